@@ -66,7 +66,7 @@ impl ApplicationHandler for App {
                 println!("in async : Loading");
                 let gfx = gfx::Gfx::new(main_owner_window.clone()).await;
                 println!("in async : Ready");
-                return gfx;
+                gfx
             });
             println!("out of async");
             self.render.entry = RenderEntry::Ready(gfx);
@@ -1,20 +1,34 @@
+use glam::Vec3;
 use paint::PaintScene;
-use ready_paint::scene::Queue;
+use ready_paint::{camera::Camera, scene::Queue, winit::window::Window};
 
 pub struct NiceViewScene;
 impl Queue for NiceViewScene {
     fn introduce(scene: &mut ready_paint::scene::Scene) {
         scene
+            .add_ready(
+                Camera::perspective(std::f32::consts::FRAC_PI_4, 1.0, 10.0).with_look_at(
+                    Vec3::new(1.5, -5.0, 3.0),
+                    Vec3::ZERO,
+                    Vec3::Z,
+                ),
+            )
+            .add_recover::<Camera>()
             .add_ready(world::World::default())
-            .add_ready(object::Tetrahedron::default());
+            .add_ready(object::Tetrahedron::default())
+            .add_fixed_update::<object::Tetrahedron>();
         scene.add_paint::<PaintScene>();
     }
 }
 
 pub mod object;
 pub mod paint;
-mod run;
 pub mod world;
 fn main() {
-    pollster::block_on(run::run());
+    ready_paint::run(
+        |render| {
+            render.add_scene::<NiceViewScene>("check");
+        },
+        Window::default_attributes().with_title("nice_view"),
+    );
 }
@@ -1,6 +1,9 @@
 use crate::world::World;
 use glam::Mat4;
-use ready_paint::scene::{get_res, get_res_mut, return_res, HashTypeId2Data, Pass, Ready, Update};
+use ready_paint::{
+    scene::{get_res, get_res_mut, return_res, HashTypeId2Data, Pass, Ready, Update},
+    time::FixedTime,
+};
 use wgpu::util::DeviceExt;
 
 #[derive(Default)]
@@ -9,6 +12,9 @@ pub struct Tetrahedron {
     object_buffer: Option<wgpu::Buffer>,
     object_line_index_buffer: Option<wgpu::Buffer>,
     pipeline: Option<wgpu::RenderPipeline>,
+    /// rotation around y after the last two fixed steps
+    angle: f32,
+    previous_angle: f32,
 }
 
 impl Ready for Tetrahedron {
@@ -96,6 +102,8 @@ impl Ready for Tetrahedron {
                 object_buffer: Some(object_buffer),
                 object_line_index_buffer: Some(object_line_index_buffer),
                 pipeline: Some(object_pipeline),
+                angle: 0.,
+                previous_angle: 0.,
             },
         );
     }
@@ -116,20 +124,29 @@ impl<'a> Pass<'a> for Tetrahedron {
         render_pass
     }
 }
+/// fixed update, the same rotation speed whatever the frame rate
 impl Update for Tetrahedron {
-    fn update(data: &mut HashTypeId2Data, gfx: &ready_paint::gfx::Gfx) {
+    fn update(data: &mut HashTypeId2Data, _gfx: &ready_paint::gfx::Gfx) {
+        let step = get_res::<FixedTime>(data).step();
         let tetra = get_res_mut::<Self>(data);
-        let buffer = tetra.object_buffer.as_ref().unwrap();
-        let dt = gfx.delta_time;
         let rotation_speed = std::f32::consts::PI;
-        let rotation = Mat4::from_rotation_y(rotation_speed * dt);
+        tetra.previous_angle = tetra.angle;
+        tetra.angle += rotation_speed * step;
+    }
+}
+impl Tetrahedron {
+    /// upload the rotation interpolated between the last two fixed steps
+    pub fn write_vertices(data: &mut HashTypeId2Data, gfx: &ready_paint::gfx::Gfx) {
+        let alpha = get_res::<FixedTime>(data).alpha();
+        let tetra = get_res::<Self>(data);
+        let angle = tetra.previous_angle + (tetra.angle - tetra.previous_angle) * alpha;
+        let rotation = Mat4::from_rotation_y(angle);
         let rotated_vertices = tetra.vertices.as_ref().unwrap().map(|v| {
             let v = rotation.transform_point3(glam::Vec3::from(v));
             [v.x, v.y, v.z]
         });
-        tetra.vertices = Some(rotated_vertices);
         gfx.queue.write_buffer(
-            buffer,
+            tetra.object_buffer.as_ref().unwrap(),
             0,
             bytemuck::cast_slice(rotated_vertices.as_flattened()),
         );
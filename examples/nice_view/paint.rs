@@ -1,5 +1,8 @@
 use crate::{object::Tetrahedron, world::World};
-use ready_paint::scene::{Paint, Pass, Update};
+use ready_paint::{
+    camera::Camera,
+    scene::{Paint, Pass, Update},
+};
 pub struct PaintScene;
 
 impl Paint for PaintScene {
@@ -13,10 +16,11 @@ impl Paint for PaintScene {
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        let _ = World::update(data, gfx);
-        let _ = Tetrahedron::update(data, gfx);
+        Camera::update(data, gfx);
+        World::update(data, gfx);
+        Tetrahedron::write_vertices(data, gfx);
         {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            let rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &view,
@@ -35,7 +39,7 @@ impl Paint for PaintScene {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
-            let mut rpass = World::pass(data, rpass);
+            let rpass = World::pass(data, rpass);
             let _ = Tetrahedron::pass(data, rpass);
         }
         gfx.queue.submit(Some(encoder.finish()));
@@ -1,8 +1,8 @@
 use ready_paint::{
+    camera::Camera,
     gfx::Gfx,
-    scene::{get_res, get_res_mut, return_res, HashTypeId2Data, Pass, Ready, Update},
+    scene::{get_ref_and_mut, get_res, return_res, HashTypeId2Data, Pass, Ready, Update},
 };
-use std::f32::consts;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
     BindGroupLayoutEntry, ShaderStages,
@@ -19,10 +19,10 @@ pub struct World {
 impl Ready for World {
     fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
         println!("world ready");
-        let world = generate_matrix(600. / 400.);
+        let config = gfx.surface_config.as_ref().unwrap();
         let uniforms = Uniforms {
-            resolution: [600., 400.],
-            matrix: world.to_cols_array(),
+            resolution: [config.width as f32, config.height as f32],
+            matrix: get_res::<Camera>(data).view_proj().to_cols_array(),
             delta_time: 0.,
             _padding: 0.,
         };
@@ -72,13 +72,16 @@ impl Ready for World {
 
 impl Update for World {
     fn update(data: &mut HashTypeId2Data, gfx: &Gfx) {
-        let world = get_res_mut::<Self>(data);
-        const DELTA_TIME_OFFSET: wgpu::BufferAddress = 72;
-        world.uniforms.as_mut().unwrap().delta_time = gfx.delta_time;
+        let (camera, world) = get_ref_and_mut::<Camera, Self>(data);
+        let config = gfx.surface_config.as_ref().unwrap();
+        let uniforms = world.uniforms.as_mut().unwrap();
+        uniforms.matrix = camera.view_proj().to_cols_array();
+        uniforms.resolution = [config.width as f32, config.height as f32];
+        uniforms.delta_time = gfx.time.delta();
         gfx.queue.write_buffer(
-            &world.uniform_buffer.as_ref().unwrap(),
-            DELTA_TIME_OFFSET,
-            bytemuck::cast_slice(&[gfx.delta_time]),
+            world.uniform_buffer.as_ref().unwrap(),
+            0,
+            bytemuck::bytes_of(uniforms),
         );
     }
 }
@@ -92,17 +95,6 @@ struct Uniforms {
     _padding: f32,
 }
 
-// create a matrix for 3d world and camera viewport
-pub fn generate_matrix(aspect_ratio: f32) -> glam::Mat4 {
-    let projection = glam::Mat4::perspective_rh(consts::FRAC_PI_4, aspect_ratio, 1.0, 10.0);
-    let view = glam::Mat4::look_at_rh(
-        glam::Vec3::new(1.5f32, -5.0, 3.0),
-        glam::Vec3::ZERO,
-        glam::Vec3::Z,
-    );
-    projection * view
-}
-
 impl<'a> Pass<'a> for World {
     fn pass(
         data: &mut HashTypeId2Data,
@@ -1,6 +1,5 @@
-use gfx::LimitFPS;
 use glam::Vec2;
-use ready_paint::{time::now, *};
+use ready_paint::*;
 use scene::{get_res, return_res, HashTypeId2Data, Paint, Pass, Queue, Ready, Scene};
 use std::sync::Arc;
 use wgpu::util::DeviceExt;
@@ -41,7 +40,7 @@ impl Ready for RectScene {
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("screen vertex buffer"),
-                contents: bytemuck::cast_slice(&four_point_rect.as_slice()),
+                contents: bytemuck::cast_slice(four_point_rect.as_slice()),
                 usage: wgpu::BufferUsages::VERTEX,
             });
         let index_buffer = gfx
@@ -119,8 +118,8 @@ impl<'a> Pass<'a> for PaintScreen {
         render_pass.set_pipeline(render_pipeline);
         render_pass.set_vertex_buffer(0, vertexes_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        render_pass.draw_indexed(0..8 as u32, 0, 0..1);
-        return render_pass;
+        render_pass.draw_indexed(0..8_u32, 0, 0..1);
+        render_pass
     }
 }
 
@@ -136,7 +135,7 @@ impl Paint for PaintScreen {
                 label: Some("Render Encoder"),
             });
         {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &view,
@@ -204,7 +203,7 @@ impl ApplicationHandler for App {
             self.window = Some(main_owner_window.clone());
             let gfx = pollster::block_on(async move {
                 let gfx = gfx::Gfx::new(main_owner_window.clone()).await;
-                return gfx;
+                gfx
             });
             self.render.entry = RenderEntry::Ready(gfx);
             // add scene
@@ -213,47 +212,8 @@ impl ApplicationHandler for App {
         }
     }
     fn about_to_wait(&mut self, _: &winit::event_loop::ActiveEventLoop) {
-        if let RenderEntry::Ready(ref mut gfx) = self.render.entry {
-            let _now = now();
-
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                let delta_time = _now - gfx.last_update;
-
-                let time = *gfx.time.lock().unwrap() + delta_time.as_secs_f32();
-                if let LimitFPS::Limit(fps) = gfx.limit_fps {
-                    let frame_duration = std::time::Duration::from_secs_f32(1.0 / fps as f32);
-                    if delta_time < frame_duration {
-                        spin_sleep::sleep(frame_duration - delta_time);
-                    }
-                }
-                gfx.last_update = now();
-                *gfx.time.lock().unwrap() = time;
-                let real_delta = gfx.last_update - _now;
-                gfx.delta_time = real_delta.as_secs_f32();
-                self.window.as_ref().unwrap().request_redraw();
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                let delta_time = _now - gfx.last_update;
-                let time = *gfx.time.lock().unwrap() + delta_time as f32;
-                if let LimitFPS::Limit(fps) = gfx.limit_fps {
-                    let frame_duration = std::time::Duration::from_secs_f32(1.0 / fps as f32);
-                    if delta_time < frame_duration.as_secs_f32() {
-                        #[cfg(not(target_arch = "wasm32"))]
-                        spin_sleep::sleep(frame_duration - delta_time);
-                        #[cfg(target_arch = "wasm32")]
-                        wasm_bindgen_futures::spawn_local(async move {
-                            wasm_timer::Delay::new(frame_duration).await.unwrap();
-                        });
-                    }
-                    gfx.last_update = now();
-                    *gfx.time.lock().unwrap() = time;
-                    let real_delta = gfx.last_update - _now;
-                    gfx.delta_time = real_delta;
-                    self.window.as_ref().unwrap().request_redraw();
-                }
-            }
+        if let RenderEntry::Ready(_) = self.render.entry {
+            self.window.as_ref().unwrap().request_redraw();
         }
     }
     fn window_event(
@@ -288,7 +248,7 @@ impl ApplicationHandler for App {
             }
             winit::event::WindowEvent::RedrawRequested => match self.render.entry {
                 RenderEntry::Ready(ref mut gfx) => {
-                    if gfx.surface_config.is_none() {
+                    if gfx.surface_config.is_none() || !gfx.pace() {
                         return;
                     }
                     let _ = gfx.test();
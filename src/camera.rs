@@ -0,0 +1,527 @@
+use crate::{
+    gfx::Gfx,
+    scene::{get_res, get_res_mut, HashTypeId2Data, Pass, Ready, Recover, Update},
+};
+use glam::{Mat4, Vec3};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use wgpu::util::DeviceExt;
+#[cfg(feature = "winit")]
+use {
+    crate::input::Input,
+    winit::{event::MouseButton, keyboard::KeyCode},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// vertical field of view in radians
+    Perspective { fov_y: f32, near: f32, far: f32 },
+    /// visible height in world units, width follows the aspect ratio
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective {
+            fov_y: std::f32::consts::FRAC_PI_4,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+
+/// layout of the camera uniform, `@group(0) @binding(0)` when set by `Camera::pass`
+/// ```wgsl
+/// struct Camera {
+///     view_proj: mat4x4<f32>,
+///     view: mat4x4<f32>,
+///     proj: mat4x4<f32>,
+///     position: vec4<f32>,
+/// }
+/// ```
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_proj: [[f32; 4]; 4],
+    pub view: [[f32; 4]; 4],
+    pub proj: [[f32; 4]; 4],
+    pub position: [f32; 4],
+}
+
+/// right handed camera resource.
+/// once ready its aspect ratio follows the surface size set by `Gfx::resize`,
+/// `Camera::update` upload the uniform, `Camera::pass` bind it at group 0.
+pub struct Camera {
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+    aspect: f32,
+    /// the aspect of `Gfx::resize`, none after `Camera::set_aspect`
+    surface_aspect: Option<Arc<AtomicU32>>,
+    follow_surface: bool,
+    uniform_buffer: Option<wgpu::Buffer>,
+    bind_group_layout: Option<wgpu::BindGroupLayout>,
+    bind_group: Option<wgpu::BindGroup>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            eye: Vec3::new(0.0, 0.0, 5.0),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+            projection: Projection::default(),
+            aspect: 1.0,
+            surface_aspect: None,
+            follow_surface: true,
+            uniform_buffer: None,
+            bind_group_layout: None,
+            bind_group: None,
+        }
+    }
+}
+
+impl Camera {
+    pub fn perspective(fov_y: f32, near: f32, far: f32) -> Self {
+        Camera {
+            projection: Projection::Perspective { fov_y, near, far },
+            ..Default::default()
+        }
+    }
+
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Self {
+        Camera {
+            projection: Projection::Orthographic { height, near, far },
+            ..Default::default()
+        }
+    }
+
+    pub fn with_look_at(mut self, eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        self.eye = eye;
+        self.target = target;
+        self.up = up;
+        self
+    }
+
+    pub fn aspect(&self) -> f32 {
+        match self.surface_aspect.as_ref() {
+            Some(aspect) => f32::from_bits(aspect.load(Ordering::Relaxed)),
+            None => self.aspect,
+        }
+    }
+
+    /// stop following the surface size, e.g. when drawing offscreen
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+        self.surface_aspect = None;
+        self.follow_surface = false;
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    pub fn proj(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y, near, far } => {
+                Mat4::perspective_rh(fov_y, self.aspect(), near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let (half_w, half_h) = (height * self.aspect() / 2.0, height / 2.0);
+                Mat4::orthographic_rh(-half_w, half_w, -half_h, half_h, near, far)
+            }
+        }
+    }
+
+    pub fn view_proj(&self) -> Mat4 {
+        self.proj() * self.view()
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        let (view, proj) = (self.view(), self.proj());
+        CameraUniform {
+            view_proj: (proj * view).to_cols_array_2d(),
+            view: view.to_cols_array_2d(),
+            proj: proj.to_cols_array_2d(),
+            position: self.eye.extend(1.0).to_array(),
+        }
+    }
+
+    /// use it when building the pipeline layout of anything drawn with this camera
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        self.bind_group_layout
+            .as_ref()
+            .expect("Camera::bind_group_layout called before Camera is ready")
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        self.bind_group
+            .as_ref()
+            .expect("Camera::bind_group called before Camera is ready")
+    }
+}
+
+impl Ready for Camera {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let mut camera = Camera {
+            eye: self.eye,
+            target: self.target,
+            up: self.up,
+            projection: self.projection,
+            aspect: self.aspect,
+            surface_aspect: self.follow_surface.then(|| gfx.aspect.clone()),
+            follow_surface: self.follow_surface,
+            ..Default::default()
+        };
+        let uniform_buffer = gfx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("camera uniform buffer"),
+                contents: bytemuck::bytes_of(&camera.uniform()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("camera bind group layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<CameraUniform>() as u64,
+                            ),
+                        },
+                        count: None,
+                    }],
+                });
+        let bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("camera bind group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        camera.uniform_buffer = Some(uniform_buffer);
+        camera.bind_group_layout = Some(bind_group_layout);
+        camera.bind_group = Some(bind_group);
+        *get_res_mut::<Camera>(data) = camera;
+    }
+}
+
+impl Update for Camera {
+    fn update(data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let camera = get_res::<Camera>(data);
+        if let Some(buffer) = camera.uniform_buffer.as_ref() {
+            gfx.queue
+                .write_buffer(buffer, 0, bytemuck::bytes_of(&camera.uniform()));
+        }
+    }
+}
+
+impl<'a> Pass<'a> for Camera {
+    fn pass(
+        data: &mut HashTypeId2Data,
+        mut render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a> {
+        render_pass.set_bind_group(0, get_res::<Camera>(data).bind_group(), &[]);
+        render_pass
+    }
+}
+
+/// keep where the camera was moved to and a fixed aspect set by `Camera::set_aspect`,
+/// its Ready would go back to the registered look at and aspect
+impl Recover for Camera {
+    /// eye, target, up, projection, aspect and whether it follows the surface
+    type State = (Vec3, Vec3, Vec3, Projection, f32, bool);
+
+    fn save(data: &HashTypeId2Data) -> Self::State {
+        let camera = get_res::<Camera>(data);
+        (
+            camera.eye,
+            camera.target,
+            camera.up,
+            camera.projection,
+            camera.aspect,
+            camera.follow_surface,
+        )
+    }
+
+    fn restore(
+        data: &mut HashTypeId2Data,
+        (eye, target, up, projection, aspect, follow_surface): Self::State,
+    ) {
+        let camera = get_res_mut::<Camera>(data);
+        camera.eye = eye;
+        camera.target = target;
+        camera.up = up;
+        camera.projection = projection;
+        camera.aspect = aspect;
+        camera.follow_surface = follow_surface;
+        if !follow_surface {
+            camera.surface_aspect = None;
+        }
+    }
+}
+
+/// rotate around a target, feed it `gfx.input` with `handle_input` (or drag and scroll deltas)
+/// then `apply` to a camera
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitController {
+    pub target: Vec3,
+    pub distance: f32,
+    /// radians around the up (y) axis
+    pub yaw: f32,
+    /// radians above the horizon, clamped short of the poles
+    pub pitch: f32,
+    /// radians per pixel of drag
+    pub rotate_speed: f32,
+    /// fraction of distance per scroll line
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        OrbitController {
+            target: Vec3::ZERO,
+            distance: 5.0,
+            yaw: 0.0,
+            pitch: 0.3,
+            rotate_speed: 0.005,
+            zoom_speed: 0.1,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+        }
+    }
+}
+
+impl OrbitController {
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    pub fn rotate(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * self.rotate_speed;
+        self.pitch = (self.pitch + dy * self.rotate_speed).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// positive scroll moves closer
+    pub fn zoom(&mut self, scroll: f32) {
+        self.distance = (self.distance * (1.0 - scroll * self.zoom_speed))
+            .clamp(self.min_distance, self.max_distance);
+    }
+
+    /// move the target in the camera plane, in pixels of drag
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let (right, up) = self.basis();
+        let scale = self.distance * self.rotate_speed * 0.2;
+        self.target += (-right * dx + up * dy) * scale;
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        self.target + Vec3::new(cp * sy, sp, cp * cy) * self.distance
+    }
+
+    fn basis(&self) -> (Vec3, Vec3) {
+        let forward = (self.target - self.eye()).normalize();
+        let right = forward.cross(Vec3::Y).normalize();
+        (right, right.cross(forward))
+    }
+
+    /// left drag rotates, right or middle drag pans, vertical scroll zooms
+    #[cfg(feature = "winit")]
+    pub fn handle_input(&mut self, input: &Input) {
+        let [dx, dy] = input.cursor_delta();
+        if input.mouse_pressed(MouseButton::Left) {
+            self.rotate(dx, dy);
+        } else if input.mouse_pressed(MouseButton::Right)
+            || input.mouse_pressed(MouseButton::Middle)
+        {
+            self.pan(dx, dy);
+        }
+        self.zoom(input.scroll_delta()[1]);
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.eye();
+        camera.target = self.target;
+        camera.up = Vec3::Y;
+    }
+}
+
+/// first person camera, feed it `gfx.input` with `handle_input` (or mouse motion and movement axes)
+/// then `apply` to a camera
+#[derive(Debug, Clone, Copy)]
+pub struct FlyController {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// units per second
+    pub speed: f32,
+    /// radians per pixel of mouse motion
+    pub look_speed: f32,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        FlyController {
+            position: Vec3::new(0.0, 0.0, 5.0),
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 3.0,
+            look_speed: 0.003,
+        }
+    }
+}
+
+impl FlyController {
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * self.look_speed;
+        self.pitch = (self.pitch - dy * self.look_speed)
+            .clamp(-OrbitController::MAX_PITCH, OrbitController::MAX_PITCH);
+    }
+
+    /// axes in -1..1: forward (w/s), right (d/a), up (e/q), dt in seconds
+    pub fn fly(&mut self, forward: f32, right: f32, up: f32, dt: f32) {
+        let forward_dir = self.forward();
+        let right_dir = forward_dir.cross(Vec3::Y).normalize();
+        let movement = forward_dir * forward + right_dir * right + Vec3::Y * up;
+        if movement.length_squared() > 0.0 {
+            self.position += movement.normalize() * self.speed * dt;
+        }
+    }
+
+    /// looks around while the right button is held, moves with w/a/s/d and e/q, dt in seconds
+    #[cfg(feature = "winit")]
+    pub fn handle_input(&mut self, input: &Input, dt: f32) {
+        if input.mouse_pressed(MouseButton::Right) {
+            let [dx, dy] = input.cursor_delta();
+            self.look(dx, dy);
+        }
+        let axis = |positive, negative| {
+            input.pressed(positive) as i32 as f32 - input.pressed(negative) as i32 as f32
+        };
+        self.fly(
+            axis(KeyCode::KeyW, KeyCode::KeyS),
+            axis(KeyCode::KeyD, KeyCode::KeyA),
+            axis(KeyCode::KeyE, KeyCode::KeyQ),
+            dt,
+        );
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        Vec3::new(-cp * sy, sp, -cp * cy)
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.position;
+        camera.target = self.position + self.forward();
+        camera.up = Vec3::Y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::return_res;
+
+    fn approx(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-5
+    }
+
+    #[test]
+    fn projection_follows_the_surface_aspect() {
+        let surface = Arc::new(AtomicU32::new(2f32.to_bits()));
+        let mut camera = Camera::perspective(std::f32::consts::FRAC_PI_2, 0.1, 10.);
+        camera.surface_aspect = Some(surface.clone());
+        let proj = camera.proj();
+        assert!((proj.x_axis.x - 0.5).abs() < 1e-5 && (proj.y_axis.y - 1.).abs() < 1e-5);
+
+        // a resize is seen without any update
+        surface.store(1f32.to_bits(), Ordering::Relaxed);
+        assert!((camera.proj().x_axis.x - 1.).abs() < 1e-5);
+
+        camera.set_aspect(4.);
+        surface.store(2f32.to_bits(), Ordering::Relaxed);
+        assert_eq!(camera.aspect(), 4.);
+
+        let mut ortho = Camera::orthographic(2., 0.1, 10.);
+        ortho.set_aspect(2.);
+        let corner = ortho.view_proj().project_point3(Vec3::new(2., 1., 0.));
+        assert!((corner.x - 1.).abs() < 1e-5 && (corner.y - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn recover_keeps_a_fixed_aspect() {
+        let mut data = HashTypeId2Data::new();
+        let mut camera = Camera {
+            eye: Vec3::new(1., 2., 3.),
+            ..Default::default()
+        };
+        camera.set_aspect(4.);
+        return_res(&mut data, camera);
+        let state = Camera::save(&data);
+        // what the Ready of the registered camera leaves on the new device
+        let readied = Camera {
+            surface_aspect: Some(Arc::new(AtomicU32::new(2f32.to_bits()))),
+            ..Default::default()
+        };
+        return_res(&mut data, readied);
+        Camera::restore(&mut data, state);
+        let camera = get_res::<Camera>(&data);
+        assert_eq!(camera.eye, Vec3::new(1., 2., 3.));
+        assert_eq!(camera.aspect(), 4.);
+        assert!(!camera.follow_surface);
+    }
+
+    #[test]
+    fn orbit() {
+        let mut orbit = OrbitController {
+            pitch: 0.,
+            ..Default::default()
+        };
+        assert!(approx(orbit.eye(), Vec3::new(0., 0., 5.)));
+        orbit.rotate(-std::f32::consts::FRAC_PI_2 / orbit.rotate_speed, 0.);
+        assert!(approx(orbit.eye(), Vec3::new(5., 0., 0.)));
+        orbit.rotate(0., 1e6);
+        assert_eq!(orbit.pitch, OrbitController::MAX_PITCH);
+
+        orbit.zoom(5.);
+        assert_eq!(orbit.distance, 2.5);
+        orbit.zoom(-1e6);
+        assert_eq!(orbit.distance, orbit.max_distance);
+
+        let mut camera = Camera::default();
+        orbit.apply(&mut camera);
+        assert_eq!((camera.eye, camera.target), (orbit.eye(), orbit.target));
+    }
+
+    #[test]
+    fn fly() {
+        let mut fly = FlyController::default();
+        assert!(approx(fly.forward(), Vec3::NEG_Z));
+        // diagonal movement is not faster
+        fly.fly(1., 1., 0., 1.);
+        assert!((fly.position - Vec3::new(0., 0., 5.)).length() - fly.speed < 1e-5);
+
+        let mut fly = FlyController::default();
+        fly.look(std::f32::consts::FRAC_PI_2 / fly.look_speed, 0.);
+        assert!(approx(fly.forward(), Vec3::X));
+        fly.fly(0., 0., 1., 2.);
+        assert!(approx(fly.position, Vec3::new(0., 6., 5.)));
+
+        let mut camera = Camera::default();
+        fly.apply(&mut camera);
+        assert!(approx(camera.target - camera.eye, Vec3::X));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    gfx::{DynamicBuffer, Gfx},
+    scene::{get_res, get_res_mut, HashTypeId2Data, Overlay, Ready},
+};
+
+pub type Color = [f32; 4];
+
+pub const RED: Color = [1.0, 0.0, 0.0, 1.0];
+pub const GREEN: Color = [0.0, 1.0, 0.0, 1.0];
+pub const BLUE: Color = [0.0, 0.0, 1.0, 1.0];
+pub const WHITE: Color = [1.0, 1.0, 1.0, 1.0];
+
+/// column major, same layout as `glam::Mat4::to_cols_array_2d`
+pub type Matrix = [[f32; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1., 0., 0., 0.],
+    [0., 1., 0., 0.],
+    [0., 0., 1., 0.],
+    [0., 0., 0., 1.],
+];
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct LineVertex {
+    /// clip space
+    position: [f32; 4],
+    color: Color,
+}
+
+/// immediate mode lines, call it from any Update or Paint during the frame:
+/// ```ignore
+/// let debug = get_res_mut::<DebugDraw>(data);
+/// debug.set_view_proj(camera.view_proj().to_cols_array_2d());
+/// debug.axes([0., 0., 0.], 1.);
+/// debug.rect([10., 10.], [200., 50.], debug_draw::GREEN);
+/// ```
+/// register with `scene.add_overlay(DebugDraw::default())`, everything queued is batched
+/// into one vertex buffer drawn by `scene::draw_overlays` in the Paint presenting the frame,
+/// what wasn't drawn is dropped at the end of the scene's paint.
+/// 2d calls are in screen pixels with (0, 0) at top left,
+/// 3d calls are transformed by the view projection set at the time of the call.
+pub struct DebugDraw {
+    view_proj: Matrix,
+    lines_3d: Vec<LineVertex>,
+    lines_2d: Vec<([f32; 2], Color)>,
+    vertex_buffer: Option<DynamicBuffer>,
+    pipeline: Option<wgpu::RenderPipeline>,
+    vertex_count: u32,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        DebugDraw {
+            view_proj: IDENTITY,
+            lines_3d: Vec::new(),
+            lines_2d: Vec::new(),
+            vertex_buffer: None,
+            pipeline: None,
+            vertex_count: 0,
+        }
+    }
+}
+
+impl DebugDraw {
+    /// nothing queued since the last frame
+    pub fn is_empty(&self) -> bool {
+        self.lines_3d.is_empty() && self.lines_2d.is_empty()
+    }
+
+    pub fn set_view_proj(&mut self, view_proj: Matrix) {
+        self.view_proj = view_proj;
+    }
+
+    pub fn line(&mut self, a: [f32; 3], b: [f32; 3], color: Color) {
+        for p in [a, b] {
+            let m = &self.view_proj;
+            let position =
+                [0, 1, 2, 3].map(|r| m[0][r] * p[0] + m[1][r] * p[1] + m[2][r] * p[2] + m[3][r]);
+            self.lines_3d.push(LineVertex { position, color });
+        }
+    }
+
+    pub fn aabb(&mut self, min: [f32; 3], max: [f32; 3], color: Color) {
+        let corner = |i: usize| {
+            [
+                if i & 1 == 0 { min[0] } else { max[0] },
+                if i & 2 == 0 { min[1] } else { max[1] },
+                if i & 4 == 0 { min[2] } else { max[2] },
+            ]
+        };
+        // corners differing by one bit share an edge
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    /// x red, y green, z blue
+    pub fn axes(&mut self, origin: [f32; 3], length: f32) {
+        let [x, y, z] = origin;
+        self.line(origin, [x + length, y, z], RED);
+        self.line(origin, [x, y + length, z], GREEN);
+        self.line(origin, [x, y, z + length], BLUE);
+    }
+
+    /// grid on the XZ plane centered at origin
+    pub fn grid(&mut self, size: f32, divisions: u32, color: Color) {
+        let divisions = divisions.max(1);
+        let half = size / 2.;
+        for i in 0..=divisions {
+            let t = -half + size * i as f32 / divisions as f32;
+            self.line([t, 0., -half], [t, 0., half], color);
+            self.line([-half, 0., t], [half, 0., t], color);
+        }
+    }
+
+    pub fn line_2d(&mut self, a: [f32; 2], b: [f32; 2], color: Color) {
+        self.lines_2d.push((a, color));
+        self.lines_2d.push((b, color));
+    }
+
+    pub fn rect(&mut self, min: [f32; 2], max: [f32; 2], color: Color) {
+        let corners = [min, [max[0], min[1]], max, [min[0], max[1]]];
+        for i in 0..4 {
+            self.line_2d(corners[i], corners[(i + 1) % 4], color);
+        }
+    }
+
+    pub fn circle(&mut self, center: [f32; 2], radius: f32, color: Color) {
+        const SEGMENTS: usize = 32;
+        let point = |i: usize| {
+            let a = std::f32::consts::TAU * i as f32 / SEGMENTS as f32;
+            [center[0] + radius * a.cos(), center[1] + radius * a.sin()]
+        };
+        for i in 0..SEGMENTS {
+            self.line_2d(point(i), point(i + 1), color);
+        }
+    }
+
+    /// drop everything queued in this frame
+    pub fn clear(&mut self) {
+        self.lines_3d.clear();
+        self.lines_2d.clear();
+    }
+}
+
+impl Ready for DebugDraw {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let shader = gfx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("debug draw shader"),
+                source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SHADER)),
+            });
+        let pipeline = gfx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("debug draw pipeline"),
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<LineVertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4],
+                    }],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: gfx.surface_config.as_ref().unwrap().view_formats[0],
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::LineList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+        let vertex_buffer = DynamicBuffer::new(
+            &gfx.device,
+            "debug draw vertex buffer",
+            wgpu::BufferUsages::VERTEX,
+            1024 * std::mem::size_of::<LineVertex>() as wgpu::BufferAddress,
+        );
+        let debug = get_res_mut::<DebugDraw>(data);
+        debug.pipeline = Some(pipeline);
+        debug.vertex_buffer = Some(vertex_buffer);
+    }
+}
+
+impl Overlay for DebugDraw {
+    fn prepare(data: &mut HashTypeId2Data, gfx: &Gfx, encoder: &mut wgpu::CommandEncoder) {
+        let debug = get_res_mut::<DebugDraw>(data);
+        let (width, height) = gfx
+            .surface_config
+            .as_ref()
+            .map(|c| (c.width as f32, c.height as f32))
+            .unwrap_or((1., 1.));
+        let mut vertices = std::mem::take(&mut debug.lines_3d);
+        vertices.extend(debug.lines_2d.drain(..).map(|([x, y], color)| LineVertex {
+            position: [x / width * 2. - 1., 1. - y / height * 2., 0., 1.],
+            color,
+        }));
+        debug.vertex_count = vertices.len() as u32;
+        if let Some(buffer) = debug.vertex_buffer.as_mut() {
+            buffer.write_staged(gfx, encoder, bytemuck::cast_slice(&vertices));
+        }
+        vertices.clear();
+        debug.lines_3d = vertices;
+    }
+
+    fn clear(data: &mut HashTypeId2Data) {
+        get_res_mut::<DebugDraw>(data).clear();
+    }
+
+    fn draw<'a>(
+        data: &mut HashTypeId2Data,
+        mut render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a> {
+        let debug = get_res::<DebugDraw>(data);
+        if debug.vertex_count == 0 {
+            return render_pass;
+        }
+        render_pass.set_pipeline(debug.pipeline.as_ref().unwrap());
+        render_pass.set_vertex_buffer(0, debug.vertex_buffer.as_ref().unwrap().slice());
+        render_pass.draw(0..debug.vertex_count, 0..1);
+        render_pass
+    }
+}
+
+const SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec4<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = position;
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let mut debug = DebugDraw::default();
+        debug.aabb([-1., -1., -1.], [1., 1., 1.], WHITE);
+        assert_eq!(debug.lines_3d.len(), 12 * 2);
+        debug.axes([0., 0., 0.], 1.);
+        debug.grid(2., 4, WHITE);
+        assert_eq!(debug.lines_3d.len(), (12 + 3 + 5 * 2) * 2);
+        debug.rect([0., 0.], [10., 10.], WHITE);
+        assert_eq!(debug.lines_2d.len(), 4 * 2);
+        debug.clear();
+        assert!(debug.lines_3d.is_empty() && debug.lines_2d.is_empty());
+    }
+
+    #[test]
+    fn view_proj() {
+        let mut debug = DebugDraw::default();
+        let mut translate = IDENTITY;
+        translate[3] = [1., 2., 3., 1.];
+        debug.set_view_proj(translate);
+        debug.line([0., 0., 0.], [1., 1., 1.], WHITE);
+        assert_eq!(debug.lines_3d[0].position, [1., 2., 3., 1.]);
+        assert_eq!(debug.lines_3d[1].position, [2., 3., 4., 1.]);
+    }
+}
@@ -0,0 +1,181 @@
+use std::marker::PhantomData;
+
+/// double buffered events between systems of a scene, register with `Scene::add_event::<T>()`.
+/// write with `send` or an `EventWriter`, read with an `EventReader` kept in the reading
+/// system's own resource:
+/// ```ignore
+/// get_res_mut::<Events<Hit>>(data).writer().send(Hit { damage: 3 });
+///
+/// let (events, health) = get_ref_and_mut::<Events<Hit>, Health>(data);
+/// for hit in events.read(&mut health.hits) {
+///     health.value -= hit.damage;
+/// }
+/// ```
+/// reading never consumes: every reader has its own cursor, so any number of systems can read
+/// the same events, each seeing them once. a system should keep one reader per `Events<T>`,
+/// sharing a reader between systems splits the events between them.
+/// `Scene::paint` swap the buffers after its paints, so an event is seen by every system once
+/// whether it runs before or after the sender, then dropped a frame later
+pub struct Events<T> {
+    previous: Vec<T>,
+    current: Vec<T>,
+    /// id of the first event in `previous` and `current`
+    previous_start: usize,
+    current_start: usize,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Events {
+            previous: Vec::new(),
+            current: Vec::new(),
+            previous_start: 0,
+            current_start: 0,
+        }
+    }
+}
+
+impl<T> Events<T> {
+    pub fn send(&mut self, event: T) {
+        self.current.push(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        self.current.extend(events);
+    }
+
+    /// a handle that can only send, to pass to code that shouldn't read or clear the events
+    pub fn writer(&mut self) -> EventWriter<'_, T> {
+        EventWriter { events: self }
+    }
+
+    /// a reader that sees every event still kept, use `reader_from_now` to skip them
+    pub fn reader(&self) -> EventReader<T> {
+        EventReader {
+            cursor: self.previous_start,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn reader_from_now(&self) -> EventReader<T> {
+        EventReader {
+            cursor: self.end(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// events the reader hasn't seen yet, oldest first
+    pub fn read<'a>(&'a self, reader: &mut EventReader<T>) -> impl Iterator<Item = &'a T> + 'a {
+        let skip_previous = reader.cursor.saturating_sub(self.previous_start);
+        let skip_current = reader.cursor.saturating_sub(self.current_start);
+        reader.cursor = self.end();
+        self.previous
+            .iter()
+            .skip(skip_previous)
+            .chain(self.current.iter().skip(skip_current))
+    }
+
+    /// events kept in both buffers
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// swap the buffers, dropping the events of the frame before the last one
+    pub fn update(&mut self) {
+        self.previous_start = self.current_start;
+        self.current_start = self.end();
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.current_start = self.end();
+        self.previous_start = self.current_start;
+        self.previous.clear();
+        self.current.clear();
+    }
+
+    fn end(&self) -> usize {
+        self.current_start + self.current.len()
+    }
+}
+
+/// cursor of one reader into `Events<T>`, readers don't affect each other
+pub struct EventReader<T> {
+    cursor: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EventReader<T> {
+    /// start at the first event ever sent, so it reads whatever is still kept
+    fn default() -> Self {
+        EventReader {
+            cursor: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// send only access to `Events<T>`, from `Events::writer`
+pub struct EventWriter<'a, T> {
+    events: &'a mut Events<T>,
+}
+
+impl<T> EventWriter<'_, T> {
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        self.events.send_batch(events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readers() {
+        let mut events = Events::default();
+        let mut early = EventReader::default();
+        let mut late = EventReader::default();
+        events.send(1);
+        events.send(2);
+        assert_eq!(events.read(&mut early).collect::<Vec<_>>(), [&1, &2]);
+        assert_eq!(events.read(&mut early).count(), 0);
+        events.update();
+        events.send(3);
+        assert_eq!(events.read(&mut early).collect::<Vec<_>>(), [&3]);
+        assert_eq!(events.read(&mut late).collect::<Vec<_>>(), [&1, &2, &3]);
+        let mut now = events.reader_from_now();
+        events.update();
+        // 1 and 2 are dropped, 3 is kept one more frame
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.reader().cursor, 2);
+        assert_eq!(events.read(&mut now).count(), 0);
+        events.send(4);
+        assert_eq!(events.read(&mut now).collect::<Vec<_>>(), [&4]);
+        events.update();
+        events.update();
+        assert!(events.is_empty());
+        assert_eq!(events.read(&mut late).count(), 0);
+    }
+
+    #[test]
+    fn writer_feeds_every_reader() {
+        let mut events = Events::default();
+        let mut first = events.reader();
+        let mut second = events.reader();
+        let mut writer = events.writer();
+        writer.send(1);
+        writer.send_batch([2, 3]);
+        assert_eq!(events.read(&mut first).collect::<Vec<_>>(), [&1, &2, &3]);
+        assert_eq!(events.read(&mut second).collect::<Vec<_>>(), [&1, &2, &3]);
+        assert_eq!(events.read(&mut first).count(), 0);
+    }
+}
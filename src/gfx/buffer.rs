@@ -0,0 +1,136 @@
+use super::Gfx;
+
+/// gpu buffer that reallocates itself when more data is written than it can hold.
+/// `write` return true when the inner `wgpu::Buffer` was replaced,
+/// any bind group pointing to the old buffer must be rebuilt then.
+pub struct DynamicBuffer {
+    buffer: wgpu::Buffer,
+    label: String,
+    usage: wgpu::BufferUsages,
+    len: wgpu::BufferAddress,
+}
+
+impl DynamicBuffer {
+    /// `COPY_DST` is always added to `usage`
+    pub fn new(
+        device: &wgpu::Device,
+        label: impl Into<String>,
+        usage: wgpu::BufferUsages,
+        capacity: wgpu::BufferAddress,
+    ) -> Self {
+        let label = label.into();
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        DynamicBuffer {
+            buffer: create_buffer(device, &label, usage, align(capacity.max(1))),
+            label,
+            usage,
+            len: 0,
+        }
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// the written part of the buffer, use it for `set_vertex_buffer` and friends.
+    /// wgpu doesn't allow empty slices, check `is_empty` first
+    pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.buffer.slice(..self.len)
+    }
+
+    /// bytes written by the last `write`
+    pub fn len(&self) -> wgpu::BufferAddress {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> wgpu::BufferAddress {
+        self.buffer.size()
+    }
+
+    /// replace the content through `queue.write_buffer`, `len` stays the data length but the copy
+    /// is padded with zeros to a multiple of `wgpu::COPY_BUFFER_ALIGNMENT` (4) as wgpu requires
+    pub fn write(&mut self, gfx: &Gfx, data: &[u8]) -> bool {
+        let reallocated = self.reserve(&gfx.device, data.len() as wgpu::BufferAddress);
+        let tail = data.len() % wgpu::COPY_BUFFER_ALIGNMENT as usize;
+        if tail == 0 {
+            if !data.is_empty() {
+                gfx.queue.write_buffer(&self.buffer, 0, data);
+            }
+        } else {
+            let mut padded = data.to_vec();
+            padded.resize(data.len() + wgpu::COPY_BUFFER_ALIGNMENT as usize - tail, 0);
+            gfx.queue.write_buffer(&self.buffer, 0, &padded);
+        }
+        self.len = data.len() as wgpu::BufferAddress;
+        reallocated
+    }
+
+    /// replace the content through the staging belt of `Gfx` into `encoder`,
+    /// the copy happens when the encoder is submitted by `Gfx::end_frame`.
+    /// padded like `write`, see `Gfx::upload`
+    pub fn write_staged(
+        &mut self,
+        gfx: &Gfx,
+        encoder: &mut wgpu::CommandEncoder,
+        data: &[u8],
+    ) -> bool {
+        let reallocated = self.reserve(&gfx.device, data.len() as wgpu::BufferAddress);
+        gfx.upload(encoder, &self.buffer, 0, data);
+        self.len = data.len() as wgpu::BufferAddress;
+        reallocated
+    }
+
+    /// make sure `size` bytes fit, old content is not kept
+    pub fn reserve(&mut self, device: &wgpu::Device, size: wgpu::BufferAddress) -> bool {
+        if size <= self.capacity() {
+            return false;
+        }
+        let capacity = grow_capacity(self.capacity(), size);
+        self.buffer = create_buffer(device, &self.label, self.usage, capacity);
+        true
+    }
+}
+
+fn create_buffer(
+    device: &wgpu::Device,
+    label: &str,
+    usage: wgpu::BufferUsages,
+    size: wgpu::BufferAddress,
+) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size,
+        usage,
+        mapped_at_creation: false,
+    })
+}
+
+/// double until it fits, so a steady growing stream only reallocates log(n) times
+fn grow_capacity(current: wgpu::BufferAddress, needed: wgpu::BufferAddress) -> wgpu::BufferAddress {
+    let mut capacity = current.max(wgpu::COPY_BUFFER_ALIGNMENT);
+    while capacity < needed {
+        capacity *= 2;
+    }
+    align(capacity)
+}
+
+fn align(size: wgpu::BufferAddress) -> wgpu::BufferAddress {
+    size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::grow_capacity;
+
+    #[test]
+    fn grow() {
+        assert_eq!(grow_capacity(64, 10), 64);
+        assert_eq!(grow_capacity(64, 65), 128);
+        assert_eq!(grow_capacity(64, 1000), 1024);
+        assert_eq!(grow_capacity(0, 3), 4);
+    }
+}
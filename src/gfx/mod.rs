@@ -1,18 +1,169 @@
-use crate::time::{now, TimeStamp};
-use std::sync::Arc;
-use wgpu::{Backends, RequestAdapterOptions, SurfaceTarget};
+use crate::time::Time;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc, Mutex,
+};
+use wgpu::{util::StagingBelt, RequestAdapterOptions, SurfaceTarget};
+
+mod buffer;
+mod pacer;
+mod profiler;
+mod stats;
+pub use buffer::DynamicBuffer;
+pub use pacer::FramePacer;
+use profiler::{FrameProfile, Profiler};
+pub use profiler::{ProfileEntry, ProfileReport};
+pub use stats::{FrameStats, FrameSummary};
+
+/// chunk size of the staging belt, uploads bigger than this get their own chunk
+const STAGING_CHUNK_SIZE: wgpu::BufferAddress = 1 << 16;
+
 pub struct Gfx {
     pub adapter: wgpu::Adapter,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub surface: wgpu::Surface<'static>,
     pub surface_config: Option<wgpu::SurfaceConfiguration>,
-    pub last_update: TimeStamp,
-    pub time: Arc<std::sync::Mutex<f32>>,
+    /// advanced once per frame by `Render::paint`
+    pub time: Time,
     pub limit_fps: LimitFPS,
-    pub fps_history: Vec<f32>,
-    pub delta_time: f32,
+    /// enforce `limit_fps` in `Gfx::pace`
+    pub pacer: FramePacer,
+    /// fed by `Render::handle_event`
+    #[cfg(feature = "winit")]
+    pub input: crate::input::Input,
+    /// wall frame times pushed by `Render::paint`
+    pub fps_history: FrameStats,
+    staging_belt: Mutex<StagingBelt>,
+    profiler: Mutex<Profiler>,
+    pub(crate) mipmaps: Mutex<crate::texture::MipmapPipelines>,
+    /// scopes of the current `Render::paint`, lent to its compute encoder and frames
+    frame_profile: Mutex<Option<FrameProfile>>,
+    instance: wgpu::Instance,
+    device_lost: Arc<AtomicBool>,
+    /// f32 bits of width / height stored by `Gfx::resize`, shared with the cameras following it
+    pub(crate) aspect: Arc<AtomicU32>,
+}
+
+/// one surface texture and the encoder recording into it,
+/// get it from `Gfx::begin_frame` and hand it back to `Gfx::end_frame`
+pub struct Frame {
+    pub output: wgpu::SurfaceTexture,
+    pub view: wgpu::TextureView,
+    pub encoder: wgpu::CommandEncoder,
+    /// the first render pass begun by `Frame::begin_render_pass` clears with it, later ones load
+    pub clear_color: Option<wgpu::Color>,
+    profile: Option<FrameProfile>,
 }
+
+impl Frame {
+    /// passes begun after it are reported under this scene name when profiling
+    pub fn enter_scene(&mut self, name: &str) {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.enter_scene(name);
+        }
+    }
+
+    /// when profiling, label is the system name the pass is reported under
+    pub fn begin_render_pass(&mut self, label: &str) -> wgpu::RenderPass<'_> {
+        let load = match self.clear_color.take() {
+            Some(color) => wgpu::LoadOp::Clear(color),
+            None => wgpu::LoadOp::Load,
+        };
+        self.begin_pass(label, load)
+    }
+
+    /// keeps what was drawn before whatever `clear_color` is, for `scene::draw_overlays`
+    pub(crate) fn begin_load_pass(&mut self, label: &str) -> wgpu::RenderPass<'_> {
+        self.begin_pass(label, wgpu::LoadOp::Load)
+    }
+
+    fn begin_pass(&mut self, label: &str, load: wgpu::LoadOp<wgpu::Color>) -> wgpu::RenderPass<'_> {
+        let timestamp_writes = self
+            .profile
+            .as_mut()
+            .and_then(|profile| profile.begin(label))
+            .map(|(query_set, index)| wgpu::RenderPassTimestampWrites {
+                query_set,
+                beginning_of_pass_write_index: Some(index),
+                end_of_pass_write_index: Some(index + 1),
+            });
+        self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes,
+            occlusion_query_set: None,
+        })
+    }
+
+    pub fn begin_compute_pass(&mut self, label: &str) -> wgpu::ComputePass<'_> {
+        begin_compute_pass(&mut self.encoder, &mut self.profile, label)
+    }
+
+    /// call it once the last begun pass is dropped, closes its cpu time when there is no `TIMESTAMP_QUERY`
+    pub fn end_pass(&mut self) {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.end();
+        }
+    }
+}
+
+/// encoder of compute work without a frame, get it from `Gfx::begin_compute`
+/// and submit it with `Gfx::end_compute`. `Render::paint` use one per registered compute,
+/// finished with `Gfx::finish_compute` and submitted together per scene
+pub struct ComputeEncoder {
+    pub encoder: wgpu::CommandEncoder,
+    profile: Option<FrameProfile>,
+}
+
+impl ComputeEncoder {
+    /// computes begun after it are reported under this scene name when profiling
+    pub fn enter_scene(&mut self, name: &str) {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.enter_scene(name);
+        }
+    }
+
+    /// when profiling, label is the system name the compute is reported under
+    pub fn begin_compute_pass(&mut self, label: &str) -> wgpu::ComputePass<'_> {
+        begin_compute_pass(&mut self.encoder, &mut self.profile, label)
+    }
+
+    /// call it once the last begun pass is dropped, like `Frame::end_pass`
+    pub fn end_pass(&mut self) {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.end();
+        }
+    }
+}
+
+fn begin_compute_pass<'e>(
+    encoder: &'e mut wgpu::CommandEncoder,
+    profile: &mut Option<FrameProfile>,
+    label: &str,
+) -> wgpu::ComputePass<'e> {
+    let timestamp_writes = profile
+        .as_mut()
+        .and_then(|profile| profile.begin(label))
+        .map(|(query_set, index)| wgpu::ComputePassTimestampWrites {
+            query_set,
+            beginning_of_pass_write_index: Some(index),
+            end_of_pass_write_index: Some(index + 1),
+        });
+    encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+        label: Some(label),
+        timestamp_writes,
+    })
+}
+
 impl Gfx {
     pub async fn new(window: impl Into<SurfaceTarget<'static>>) -> Self {
         #[cfg(target_arch = "wasm32")]
@@ -23,40 +174,99 @@ impl Gfx {
         #[cfg(target_arch = "wasm32")]
         let instance = {
             wgpu::Instance::new(&wgpu::InstanceDescriptor {
-                backends: Backends::BROWSER_WEBGPU,
+                backends: wgpu::Backends::BROWSER_WEBGPU,
                 ..Default::default()
             })
         };
         #[cfg(not(target_arch = "wasm32"))]
         let instance = wgpu::Instance::default();
 
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptions::default())
-            .await
-            .unwrap();
-
-        let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default(), None)
+        Self::create(instance, window)
             .await
-            .unwrap();
+            .unwrap_or_else(|e| panic!("Gfx::new: {}", e))
+    }
 
-        let surface = instance.create_surface(window).unwrap();
+    async fn create(
+        instance: wgpu::Instance,
+        window: impl Into<SurfaceTarget<'static>>,
+    ) -> Result<Self, GfxError> {
+        let (adapter, device, queue, surface, device_lost) = request_gpu(&instance, window).await?;
+        let profiler = Profiler::new(&device, &queue);
 
-        Gfx {
+        Ok(Gfx {
             device,
             queue,
             surface,
             adapter,
             surface_config: None,
-            last_update: now(),
-            time: Arc::new(std::sync::Mutex::new(0.0)),
+            time: Time::default(),
             limit_fps: LimitFPS::default(),
-            fps_history: Vec::new(),
-            delta_time: 0.0,
+            pacer: FramePacer::default(),
+            #[cfg(feature = "winit")]
+            input: crate::input::Input::default(),
+            fps_history: FrameStats::default(),
+            staging_belt: Mutex::new(StagingBelt::new(STAGING_CHUNK_SIZE)),
+            profiler: Mutex::new(profiler),
+            mipmaps: Mutex::default(),
+            frame_profile: Mutex::default(),
+            instance,
+            device_lost,
+            aspect: Arc::new(AtomicU32::new(1f32.to_bits())),
+        })
+    }
+
+    /// set by the device lost callback, call `Render::recover` (or `Gfx::recover`) once it's true
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// new adapter, device, queue and surface on window, configured to the previous size.
+    /// the old surface and device are dropped first, everything created from them is dead,
+    /// `Render::recover` also run the readys again
+    pub async fn recover(
+        self,
+        window: impl Into<SurfaceTarget<'static>>,
+    ) -> Result<Self, GfxError> {
+        let profiling = self.profiler.lock().unwrap().enabled;
+        let size = self.surface_config.as_ref().map(|c| (c.width, c.height));
+        let Gfx {
+            adapter,
+            device,
+            queue,
+            surface,
+            instance,
+            time,
+            limit_fps,
+            pacer,
+            #[cfg(feature = "winit")]
+            input,
+            fps_history,
+            aspect,
+            ..
+        } = self;
+        // a window has one surface at a time
+        drop(surface);
+        drop((queue, device, adapter));
+        let mut gfx = Self::create(instance, window).await?;
+        gfx.set_profiling(profiling);
+        gfx.time = time;
+        gfx.limit_fps = limit_fps;
+        gfx.pacer = pacer;
+        #[cfg(feature = "winit")]
+        {
+            gfx.input = input;
         }
+        gfx.fps_history = fps_history;
+        // cameras keep following the same aspect
+        gfx.aspect = aspect;
+        if let Some((width, height)) = size {
+            gfx.resize(width, height);
+        }
+        Ok(gfx)
     }
+
     pub fn set_zero_dt(&mut self) {
-        self.delta_time = 0.;
+        self.time.zero_delta();
     }
     pub fn with_fps(mut self, fps: f32) -> Self {
         self.limit_fps = LimitFPS::Limit(fps);
@@ -67,6 +277,29 @@ impl Gfx {
         self
     }
 
+    /// call it before `Render::paint` and only paint when true.
+    /// native: block until the frame is due under `limit_fps` then return true,
+    /// wasm: return false for a `requestAnimationFrame` that comes too early, request the redraw again then
+    pub fn pace(&mut self) -> bool {
+        self.pacer.pace(&self.limit_fps)
+    }
+
+    /// time every compute and every `Frame` pass of `Render::paint`, read it with `Gfx::profile_report`
+    pub fn with_profiling(mut self) -> Self {
+        self.set_profiling(true);
+        self
+    }
+
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiler.get_mut().unwrap().enabled = enabled;
+    }
+
+    /// latest finished frame, gpu timestamps arrive a few frames late
+    pub fn profile_report(&self) -> Option<ProfileReport> {
+        self.profiler.lock().unwrap().report().cloned()
+    }
+
+    /// also sets the aspect ratio of every `Camera` following the surface
     pub fn resize(&mut self, width: u32, height: u32) {
         let mut surface_config = self
             .surface
@@ -76,6 +309,153 @@ impl Gfx {
         let view_format = surface_config.format.add_srgb_suffix();
         surface_config.view_formats.push(view_format);
         self.surface_config = Some(surface_config);
+        if width > 0 && height > 0 {
+            self.aspect
+                .store((width as f32 / height as f32).to_bits(), Ordering::Relaxed);
+        }
+        #[cfg(feature = "winit")]
+        self.input.set_window_size(width, height);
+    }
+
+    pub fn begin_frame(&self) -> Result<Frame, wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Frame Encoder"),
+            });
+        Ok(Frame {
+            output,
+            view,
+            encoder,
+            clear_color: None,
+            profile: self.frame_profile.lock().unwrap().take(),
+        })
+    }
+
+    /// submit the frame encoder with all staged uploads, then present
+    pub fn end_frame(&self, frame: Frame) {
+        let Frame {
+            output,
+            encoder,
+            profile,
+            ..
+        } = frame;
+        self.submit(encoder);
+        if profile.is_some() {
+            *self.frame_profile.lock().unwrap() = profile;
+        }
+        output.present();
+    }
+
+    /// encoder for compute work outside of a frame, no surface texture is acquired
+    pub fn begin_compute(&self) -> ComputeEncoder {
+        let encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Compute Encoder"),
+            });
+        ComputeEncoder {
+            encoder,
+            profile: self.frame_profile.lock().unwrap().take(),
+        }
+    }
+
+    /// submit the compute encoder with all staged uploads
+    pub fn end_compute(&self, computes: ComputeEncoder) {
+        let command_buffer = self.finish_compute(computes);
+        self.submit_command_buffers(Some(command_buffer));
+    }
+
+    /// finish the compute encoder without submitting it, to batch several in one submit
+    pub fn finish_compute(&self, computes: ComputeEncoder) -> wgpu::CommandBuffer {
+        let ComputeEncoder { encoder, profile } = computes;
+        if profile.is_some() {
+            *self.frame_profile.lock().unwrap() = profile;
+        }
+        encoder.finish()
+    }
+
+    /// start measuring the passes of one `Render::paint`
+    pub(crate) fn begin_profile(&self) {
+        *self.frame_profile.lock().unwrap() = self.profiler.lock().unwrap().begin_frame();
+    }
+
+    /// passes and systems after it are reported under this scene name when profiling
+    pub(crate) fn enter_scene(&self, name: &str) {
+        if let Some(profile) = self.frame_profile.lock().unwrap().as_mut() {
+            profile.enter_scene(name);
+        }
+    }
+
+    /// resolve what the computes and frames of this paint measured, then read back finished reports
+    pub(crate) fn end_profile(&self) {
+        let mut profiler = self.profiler.lock().unwrap();
+        if let Some(profile) = self.frame_profile.lock().unwrap().take() {
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Profiler Encoder"),
+                });
+            profiler.end_frame(&self.device, &mut encoder, profile);
+            self.queue.submit(Some(encoder.finish()));
+        }
+        if profiler.enabled {
+            profiler.poll(&self.device);
+        }
+    }
+
+    /// submit an encoder that may contain staged uploads from `Gfx::upload`.
+    /// don't call it while a `Frame` or `ComputeEncoder` is open (e.g. an upload encoder inside
+    /// a Paint that already began its frame): the uploads already staged in the open one would
+    /// run after this submit's. `Render::paint` recalls the belt chunks once, after its last submit
+    pub fn submit(&self, encoder: wgpu::CommandEncoder) {
+        self.submit_command_buffers(Some(encoder.finish()));
+    }
+
+    /// `Gfx::submit` for finished encoders, in one queue submit
+    pub fn submit_command_buffers(
+        &self,
+        command_buffers: impl IntoIterator<Item = wgpu::CommandBuffer>,
+    ) {
+        self.staging_belt.lock().unwrap().finish();
+        self.queue.submit(command_buffers);
+    }
+
+    /// reuse the staging belt chunks, only once every encoder holding uploads is submitted
+    pub(crate) fn recall_staging(&self) {
+        self.staging_belt.lock().unwrap().recall();
+    }
+
+    /// copy data into buffer at offset through the staging belt,
+    /// batched into encoder instead of a separate `queue.write_buffer` each call.
+    /// the encoder must be submitted by `Gfx::submit` or `Gfx::end_frame`.
+    /// offset must be a multiple of `wgpu::COPY_BUFFER_ALIGNMENT` (4), the copy is padded with zeros
+    /// to a multiple of it so the buffer needs room for that, empty data does nothing
+    pub fn upload(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        buffer: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        data: &[u8],
+    ) {
+        assert!(
+            offset & (wgpu::COPY_BUFFER_ALIGNMENT - 1) == 0,
+            "Gfx::upload offset {} is not a multiple of wgpu::COPY_BUFFER_ALIGNMENT",
+            offset
+        );
+        let padded =
+            (data.len() as wgpu::BufferAddress).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        let Some(size) = wgpu::BufferSize::new(padded) else {
+            return;
+        };
+        let mut belt = self.staging_belt.lock().unwrap();
+        let mut view = belt.write_buffer(encoder, buffer, offset, size, &self.device);
+        view[..data.len()].copy_from_slice(data);
+        view[data.len()..].fill(0);
     }
 
     pub fn test(&self) -> Result<(), wgpu::SurfaceError> {
@@ -118,6 +498,82 @@ impl Gfx {
     }
 }
 
+async fn request_gpu(
+    instance: &wgpu::Instance,
+    window: impl Into<SurfaceTarget<'static>>,
+) -> Result<
+    (
+        wgpu::Adapter,
+        wgpu::Device,
+        wgpu::Queue,
+        wgpu::Surface<'static>,
+        Arc<AtomicBool>,
+    ),
+    GfxError,
+> {
+    let surface = instance.create_surface(window).map_err(GfxError::Surface)?;
+
+    let adapter = instance
+        .request_adapter(&RequestAdapterOptions {
+            compatible_surface: Some(&surface),
+            ..Default::default()
+        })
+        .await
+        .ok_or(GfxError::NoAdapter)?;
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                // for the profiler, only when the adapter has it
+                required_features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .map_err(GfxError::Device)?;
+
+    let device_lost = Arc::new(AtomicBool::new(false));
+    let lost = device_lost.clone();
+    device.set_device_lost_callback(move |reason, message| {
+        tracing::error!("Gfx device lost ({:?}): {}", reason, message);
+        lost.store(true, Ordering::Relaxed);
+    });
+
+    Ok((adapter, device, queue, surface, device_lost))
+}
+
+/// the gpu state couldn't be built on the window, see `Gfx::recover`
+#[derive(Debug)]
+pub enum GfxError {
+    Surface(wgpu::CreateSurfaceError),
+    NoAdapter,
+    Device(wgpu::RequestDeviceError),
+    /// there is no gfx to recover (`RenderEntry::NotReady`), e.g. after a failed recover
+    NotReady,
+}
+
+impl std::fmt::Display for GfxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GfxError::Surface(e) => write!(f, "can't create the surface: {}", e),
+            GfxError::NoAdapter => write!(f, "no adapter compatible with the surface"),
+            GfxError::Device(e) => write!(f, "can't request the device: {}", e),
+            GfxError::NotReady => write!(f, "gfx is not ready"),
+        }
+    }
+}
+
+impl std::error::Error for GfxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GfxError::Surface(e) => Some(e),
+            GfxError::NoAdapter | GfxError::NotReady => None,
+            GfxError::Device(e) => Some(e),
+        }
+    }
+}
+
 #[derive(PartialEq)]
 pub enum LimitFPS {
     Limit(f32),
@@ -0,0 +1,110 @@
+use super::LimitFPS;
+use crate::time::{now, seconds_since, TimeStamp};
+use std::collections::VecDeque;
+
+/// frame times kept by the pacer
+const HISTORY: usize = 120;
+/// on the web a frame counts as due this early, so a 30 fps limit on a 60 Hz
+/// `requestAnimationFrame` doesn't miss every other vsync by a fraction of a millisecond
+#[cfg(target_arch = "wasm32")]
+const WEB_TOLERANCE: f32 = 0.004;
+
+/// enforce `LimitFPS`, call `Gfx::pace` right before `Render::paint`.
+/// native: sleep most of the remaining time then spin the last `spin_threshold`, sleep overshoots.
+/// wasm: never block, frames come from `requestAnimationFrame` and the ones due too early are skipped.
+/// it keeps the last frame times it paced, the statistics of every painted frame are in `gfx.fps_history`
+pub struct FramePacer {
+    /// seconds spun instead of slept before the frame is due
+    pub spin_threshold: f32,
+    last_frame: Option<TimeStamp>,
+    frame_times: VecDeque<f32>,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        FramePacer {
+            spin_threshold: 0.002,
+            last_frame: None,
+            frame_times: VecDeque::with_capacity(HISTORY),
+        }
+    }
+}
+
+impl FramePacer {
+    /// true when a frame should be drawn now, native always ends up true after waiting
+    pub fn pace(&mut self, limit: &LimitFPS) -> bool {
+        let target = match limit {
+            LimitFPS::Limit(fps) if *fps > 0. => 1. / fps,
+            _ => 0.,
+        };
+        if let Some(last_frame) = self.last_frame {
+            #[cfg(not(target_arch = "wasm32"))]
+            self.wait(last_frame, target);
+            #[cfg(target_arch = "wasm32")]
+            if seconds_since(last_frame) + WEB_TOLERANCE < target {
+                return false;
+            }
+            self.record(seconds_since(last_frame));
+        }
+        self.last_frame = Some(now());
+        true
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn wait(&self, last_frame: TimeStamp, target: f32) {
+        let remaining = target - seconds_since(last_frame);
+        if remaining > self.spin_threshold {
+            std::thread::sleep(std::time::Duration::from_secs_f32(
+                remaining - self.spin_threshold,
+            ));
+        }
+        while seconds_since(last_frame) < target {
+            std::hint::spin_loop();
+        }
+    }
+
+    fn record(&mut self, frame_time: f32) {
+        if self.frame_times.len() == HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    /// seconds between the starts of the last frames, oldest first
+    pub fn frame_times(&self) -> impl Iterator<Item = f32> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    pub fn last_frame_time(&self) -> Option<f32> {
+        self.frame_times.back().copied()
+    }
+
+    /// achieved fps over the kept frame times
+    pub fn average_fps(&self) -> f32 {
+        let total: f32 = self.frame_times.iter().sum();
+        if total > 0. {
+            self.frame_times.len() as f32 / total
+        } else {
+            0.
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit() {
+        let mut pacer = FramePacer::default();
+        let limit = LimitFPS::Limit(100.);
+        let start = now();
+        for _ in 0..4 {
+            assert!(pacer.pace(&limit));
+        }
+        // first frame doesn't wait
+        assert!(seconds_since(start) >= 0.03);
+        assert_eq!(pacer.frame_times().count(), 3);
+        assert!(pacer.frame_times().all(|t| t >= 0.01));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::time::{now, seconds_since, TimeStamp};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+/// passes measured per frame, the ones after it are not in the report
+const MAX_SCOPES: u32 = 64;
+/// frames waiting for their timestamps to be read back, a frame past it is not measured
+const MAX_PENDING: usize = 3;
+const TIMESTAMPS_SIZE: wgpu::BufferAddress =
+    MAX_SCOPES as wgpu::BufferAddress * 2 * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileEntry {
+    pub scene: String,
+    /// type name of the pass, compute or overlay
+    pub system: String,
+    pub ms: f32,
+}
+
+/// time spent in each pass, compute and overlay of one frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileReport {
+    /// true when measured by gpu timestamps, false when it's the cpu time spent recording
+    pub gpu: bool,
+    pub entries: Vec<ProfileEntry>,
+}
+
+impl ProfileReport {
+    pub fn total_ms(&self) -> f32 {
+        self.entries.iter().map(|e| e.ms).sum()
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scene_width = self
+            .entries
+            .iter()
+            .map(|e| e.scene.len())
+            .fold(5, usize::max);
+        let system_width = self
+            .entries
+            .iter()
+            .map(|e| e.system.len())
+            .fold(6, usize::max);
+        let unit = if self.gpu { "gpu ms" } else { "cpu ms" };
+        writeln!(
+            f,
+            "{:<scene_width$}  {:<system_width$}  {:>9}",
+            "scene", "system", unit
+        )?;
+        for e in self.entries.iter() {
+            writeln!(
+                f,
+                "{:<scene_width$}  {:<system_width$}  {:>9.3}",
+                e.scene, e.system, e.ms
+            )?;
+        }
+        write!(
+            f,
+            "{:<scene_width$}  {:<system_width$}  {:>9.3}",
+            "",
+            "total",
+            self.total_ms()
+        )
+    }
+}
+
+/// scopes recorded during one `Render::paint`
+pub(crate) struct FrameProfile {
+    scene: String,
+    scopes: Vec<(String, String)>,
+    query_set: Option<wgpu::QuerySet>,
+    cpu_ms: Vec<f32>,
+    cpu_start: Option<TimeStamp>,
+}
+
+impl FrameProfile {
+    pub fn enter_scene(&mut self, scene: &str) {
+        scene.clone_into(&mut self.scene);
+    }
+
+    /// start a scope for the next pass, with the query set and first index to write if timed on gpu
+    pub fn begin(&mut self, system: &str) -> Option<(&wgpu::QuerySet, u32)> {
+        self.end();
+        if self.scopes.len() as u32 >= MAX_SCOPES {
+            return None;
+        }
+        let index = self.scopes.len() as u32 * 2;
+        self.scopes.push((self.scene.clone(), system.to_string()));
+        self.cpu_start = Some(now());
+        self.query_set.as_ref().map(|query_set| (query_set, index))
+    }
+
+    pub fn end(&mut self) {
+        if let Some(start) = self.cpu_start.take() {
+            self.cpu_ms.push(seconds_since(start) * 1000.);
+        }
+    }
+}
+
+struct Timestamps {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    /// readback buffers not in flight
+    free: Vec<wgpu::Buffer>,
+    /// nanoseconds per tick
+    period: f32,
+}
+
+struct Pending {
+    buffer: wgpu::Buffer,
+    scopes: Vec<(String, String)>,
+    /// set by the map callback, false if mapping failed
+    mapped: Arc<OnceLock<bool>>,
+    requested: bool,
+}
+
+/// gpu timestamps when the device has `TIMESTAMP_QUERY`, cpu recording time otherwise
+pub(crate) struct Profiler {
+    pub enabled: bool,
+    timestamps: Option<Timestamps>,
+    pending: VecDeque<Pending>,
+    report: Option<ProfileReport>,
+}
+
+impl Profiler {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let timestamps = device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+            .then(|| Timestamps {
+                query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("profiler query set"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count: MAX_SCOPES * 2,
+                }),
+                resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("profiler resolve buffer"),
+                    size: TIMESTAMPS_SIZE,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }),
+                free: Vec::new(),
+                period: queue.get_timestamp_period(),
+            });
+        Profiler {
+            enabled: false,
+            timestamps,
+            pending: VecDeque::new(),
+            report: None,
+        }
+    }
+
+    pub fn report(&self) -> Option<&ProfileReport> {
+        self.report.as_ref()
+    }
+
+    pub fn begin_frame(&self) -> Option<FrameProfile> {
+        if !self.enabled {
+            return None;
+        }
+        let query_set = match self.timestamps.as_ref() {
+            Some(_) if self.pending.len() >= MAX_PENDING => return None,
+            Some(timestamps) => Some(timestamps.query_set.clone()),
+            None => None,
+        };
+        Some(FrameProfile {
+            scene: String::new(),
+            scopes: Vec::new(),
+            query_set,
+            cpu_ms: Vec::new(),
+            cpu_start: None,
+        })
+    }
+
+    /// before submit: resolve the timestamps into a readback buffer, or report the cpu times
+    pub fn end_frame(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        mut frame: FrameProfile,
+    ) {
+        frame.end();
+        let Some(timestamps) = self
+            .timestamps
+            .as_mut()
+            .filter(|_| frame.query_set.is_some())
+        else {
+            let entries = frame
+                .scopes
+                .into_iter()
+                .zip(frame.cpu_ms)
+                .map(|((scene, system), ms)| ProfileEntry { scene, system, ms })
+                .collect();
+            self.report = Some(ProfileReport {
+                gpu: false,
+                entries,
+            });
+            return;
+        };
+        if frame.scopes.is_empty() {
+            self.report = Some(ProfileReport {
+                gpu: true,
+                entries: Vec::new(),
+            });
+            return;
+        }
+        let buffer = timestamps.free.pop().unwrap_or_else(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("profiler readback buffer"),
+                size: TIMESTAMPS_SIZE,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+        let count = frame.scopes.len() as u32 * 2;
+        let size = count as wgpu::BufferAddress * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+        encoder.resolve_query_set(
+            &timestamps.query_set,
+            0..count,
+            &timestamps.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(&timestamps.resolve_buffer, 0, &buffer, 0, size);
+        self.pending.push_back(Pending {
+            buffer,
+            scopes: frame.scopes,
+            mapped: Arc::new(OnceLock::new()),
+            requested: false,
+        });
+    }
+
+    /// after submit: map what was just resolved and turn every finished readback into a report
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        for pending in self.pending.iter_mut().filter(|p| !p.requested) {
+            let mapped = pending.mapped.clone();
+            pending
+                .buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = mapped.set(result.is_ok());
+                });
+            pending.requested = true;
+        }
+        let _ = device.poll(wgpu::Maintain::Poll);
+        let Some(timestamps) = self.timestamps.as_mut() else {
+            return;
+        };
+        while let Some(&ok) = self.pending.front().and_then(|p| p.mapped.get()) {
+            let pending = self.pending.pop_front().unwrap();
+            if ok {
+                let data = pending.buffer.slice(..).get_mapped_range();
+                let ticks: &[u64] = bytemuck::cast_slice(&data);
+                let entries = pending
+                    .scopes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (scene, system))| ProfileEntry {
+                        scene,
+                        system,
+                        ms: ticks[i * 2 + 1].saturating_sub(ticks[i * 2]) as f32
+                            * timestamps.period
+                            / 1_000_000.,
+                    })
+                    .collect();
+                drop(data);
+                pending.buffer.unmap();
+                self.report = Some(ProfileReport { gpu: true, entries });
+            }
+            timestamps.free.push(pending.buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_table() {
+        let report = ProfileReport {
+            gpu: true,
+            entries: vec![
+                ProfileEntry {
+                    scene: "main".into(),
+                    system: "Camera".into(),
+                    ms: 0.25,
+                },
+                ProfileEntry {
+                    scene: "main".into(),
+                    system: "DebugDraw".into(),
+                    ms: 0.5,
+                },
+            ],
+        };
+        assert_eq!(report.total_ms(), 0.75);
+        let table = report.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].ends_with("gpu ms"));
+        assert!(lines[2].starts_with("main   DebugDraw"));
+        assert!(lines[3].ends_with("0.750"));
+    }
+}
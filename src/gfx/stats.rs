@@ -0,0 +1,223 @@
+use std::{collections::VecDeque, fmt::Write};
+
+/// frames kept by `FrameStats::default`, about a minute at 60 fps
+const DEFAULT_CAPACITY: usize = 3600;
+
+/// ring buffer of the last frame times, `Render::paint` push one per frame into `gfx.fps_history`.
+/// frame times are in seconds, fps derived from them
+pub struct FrameStats {
+    capacity: usize,
+    frame_times: VecDeque<f32>,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        FrameStats::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// everything `FrameStats` computes, at one point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSummary {
+    pub frames: usize,
+    pub average_fps: f32,
+    pub min_fps: f32,
+    pub max_fps: f32,
+    /// average fps of the slowest 1% frames
+    pub low_1_percent_fps: f32,
+    /// average fps of the slowest 0.1% frames
+    pub low_0_1_percent_fps: f32,
+    pub std_dev_ms: f32,
+}
+
+impl FrameStats {
+    pub fn new(capacity: usize) -> Self {
+        FrameStats {
+            capacity: capacity.max(1),
+            frame_times: VecDeque::with_capacity(capacity.max(1)),
+        }
+    }
+
+    /// drop the oldest frame when full
+    pub fn push(&mut self, frame_time: f32) {
+        if self.frame_times.len() == self.capacity {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frame_times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frame_times.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.frame_times.clear();
+    }
+
+    /// oldest first
+    pub fn frame_times(&self) -> impl Iterator<Item = f32> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    pub fn last_frame_time(&self) -> Option<f32> {
+        self.frame_times.back().copied()
+    }
+
+    pub fn average_fps(&self) -> f32 {
+        fps(self.frame_times.len(), self.frame_times.iter().sum())
+    }
+
+    /// fps of the slowest frame
+    pub fn min_fps(&self) -> f32 {
+        let max = self.frame_times.iter().copied().fold(0., f32::max);
+        if max > 0. {
+            1. / max
+        } else {
+            0.
+        }
+    }
+
+    /// fps of the fastest frame
+    pub fn max_fps(&self) -> f32 {
+        let min = self
+            .frame_times
+            .iter()
+            .copied()
+            .fold(f32::INFINITY, f32::min);
+        if min.is_finite() && min > 0. {
+            1. / min
+        } else {
+            0.
+        }
+    }
+
+    /// average fps of the slowest `fraction` of frames (0.01 for the 1% low), at least one frame
+    pub fn low_fps(&self, fraction: f32) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.;
+        }
+        let mut sorted: Vec<f32> = self.frame_times.iter().copied().collect();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        let count = ((sorted.len() as f32 * fraction).ceil() as usize).clamp(1, sorted.len());
+        fps(count, sorted[..count].iter().sum())
+    }
+
+    /// standard deviation of the frame times in milliseconds
+    pub fn std_dev_ms(&self) -> f32 {
+        let len = self.frame_times.len();
+        if len < 2 {
+            return 0.;
+        }
+        let mean = self.frame_times.iter().sum::<f32>() / len as f32;
+        let variance = self
+            .frame_times
+            .iter()
+            .map(|t| (t - mean) * (t - mean))
+            .sum::<f32>()
+            / len as f32;
+        variance.sqrt() * 1000.
+    }
+
+    pub fn summary(&self) -> FrameSummary {
+        FrameSummary {
+            frames: self.len(),
+            average_fps: self.average_fps(),
+            min_fps: self.min_fps(),
+            max_fps: self.max_fps(),
+            low_1_percent_fps: self.low_fps(0.01),
+            low_0_1_percent_fps: self.low_fps(0.001),
+            std_dev_ms: self.std_dev_ms(),
+        }
+    }
+
+    /// one row per frame, `frame,frame_time_ms,fps`
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("frame,frame_time_ms,fps\n");
+        for (i, t) in self.frame_times.iter().enumerate() {
+            let _ = writeln!(csv, "{},{:.3},{:.2}", i, t * 1000., fps(1, *t));
+        }
+        csv
+    }
+
+    /// the summary and the frame times in milliseconds
+    pub fn to_json(&self) -> String {
+        let s = self.summary();
+        let frame_times: Vec<String> = self
+            .frame_times
+            .iter()
+            .map(|t| format!("{:.3}", t * 1000.))
+            .collect();
+        format!(
+            concat!(
+                "{{\"frames\":{},\"average_fps\":{:.2},\"min_fps\":{:.2},\"max_fps\":{:.2},",
+                "\"low_1_percent_fps\":{:.2},\"low_0_1_percent_fps\":{:.2},\"std_dev_ms\":{:.3},",
+                "\"frame_times_ms\":[{}]}}"
+            ),
+            s.frames,
+            s.average_fps,
+            s.min_fps,
+            s.max_fps,
+            s.low_1_percent_fps,
+            s.low_0_1_percent_fps,
+            s.std_dev_ms,
+            frame_times.join(",")
+        )
+    }
+}
+
+fn fps(frames: usize, seconds: f32) -> f32 {
+    if seconds > 0. {
+        frames as f32 / seconds
+    } else {
+        0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameStats;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn summary() {
+        let mut stats = FrameStats::new(200);
+        for _ in 0..99 {
+            stats.push(0.01);
+        }
+        stats.push(0.1);
+        assert!(close(stats.average_fps(), 100. / 1.09));
+        assert!(close(stats.min_fps(), 10.));
+        assert!(close(stats.max_fps(), 100.));
+        assert!(close(stats.low_fps(0.01), 10.));
+        assert!(close(stats.low_fps(0.02), 2. / 0.11));
+        assert!(close(stats.std_dev_ms(), 8.955));
+    }
+
+    #[test]
+    fn ring_and_export() {
+        let mut stats = FrameStats::new(2);
+        assert_eq!(stats.summary().average_fps, 0.);
+        stats.push(0.5);
+        stats.push(0.02);
+        stats.push(0.04);
+        assert_eq!(stats.frame_times().collect::<Vec<_>>(), [0.02, 0.04]);
+        assert_eq!(
+            stats.to_csv(),
+            "frame,frame_time_ms,fps\n0,20.000,50.00\n1,40.000,25.00\n"
+        );
+        let json = stats.to_json();
+        assert!(json.starts_with("{\"frames\":2,\"average_fps\":33.33,"));
+        assert!(json.ends_with("\"frame_times_ms\":[20.000,40.000]}"));
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use winit::{
+    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
+};
+
+/// pixel scroll (touchpads) is converted to lines with it
+const PIXELS_PER_LINE: f32 = 20.;
+
+/// keyboard and mouse state read from any system through `gfx.input`.
+/// `Render::handle_event` feed it window events between frames,
+/// the `just_*` state, cursor and scroll deltas are cleared after `Render::paint`
+#[derive(Default)]
+pub struct Input {
+    keys: HashSet<KeyCode>,
+    keys_just_pressed: HashSet<KeyCode>,
+    keys_just_released: HashSet<KeyCode>,
+    buttons: HashSet<MouseButton>,
+    buttons_just_pressed: HashSet<MouseButton>,
+    buttons_just_released: HashSet<MouseButton>,
+    cursor: Option<[f32; 2]>,
+    cursor_delta: [f32; 2],
+    scroll_delta: [f32; 2],
+    window_size: [f32; 2],
+    modifiers: ModifiersState,
+}
+
+impl Input {
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                if let PhysicalKey::Code(key) = event.physical_key {
+                    match event.state {
+                        ElementState::Pressed if !event.repeat => self.press_key(key),
+                        ElementState::Released => self.release_key(key),
+                        _ => (),
+                    }
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => self.press_button(*button),
+                ElementState::Released => self.release_button(*button),
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.move_cursor([position.x as f32, position.y as f32]);
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseWheel { delta, .. } => {
+                let [x, y] = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => [*x, *y],
+                    MouseScrollDelta::PixelDelta(p) => {
+                        [p.x as f32 / PIXELS_PER_LINE, p.y as f32 / PIXELS_PER_LINE]
+                    }
+                };
+                self.scroll_delta[0] += x;
+                self.scroll_delta[1] += y;
+            }
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            // keys released while unfocused never come back
+            WindowEvent::Focused(false) => self.release_all(),
+            _ => (),
+        }
+    }
+
+    /// clear what only lasts one frame, called by `Render::paint`
+    pub fn end_frame(&mut self) {
+        self.keys_just_pressed.clear();
+        self.keys_just_released.clear();
+        self.buttons_just_pressed.clear();
+        self.buttons_just_released.clear();
+        self.cursor_delta = [0., 0.];
+        self.scroll_delta = [0., 0.];
+    }
+
+    /// kept by `Gfx::resize`
+    pub(crate) fn set_window_size(&mut self, width: u32, height: u32) {
+        self.window_size = [width as f32, height as f32];
+    }
+
+    fn press_key(&mut self, key: KeyCode) {
+        if self.keys.insert(key) {
+            self.keys_just_pressed.insert(key);
+        }
+    }
+
+    fn release_key(&mut self, key: KeyCode) {
+        if self.keys.remove(&key) {
+            self.keys_just_released.insert(key);
+        }
+    }
+
+    fn press_button(&mut self, button: MouseButton) {
+        if self.buttons.insert(button) {
+            self.buttons_just_pressed.insert(button);
+        }
+    }
+
+    fn release_button(&mut self, button: MouseButton) {
+        if self.buttons.remove(&button) {
+            self.buttons_just_released.insert(button);
+        }
+    }
+
+    fn move_cursor(&mut self, position: [f32; 2]) {
+        if let Some([x, y]) = self.cursor {
+            self.cursor_delta[0] += position[0] - x;
+            self.cursor_delta[1] += position[1] - y;
+        }
+        self.cursor = Some(position);
+    }
+
+    fn release_all(&mut self) {
+        self.keys_just_released.extend(self.keys.drain());
+        self.buttons_just_released.extend(self.buttons.drain());
+    }
+
+    pub fn pressed(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.keys_just_pressed.contains(&key)
+    }
+
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        self.keys_just_released.contains(&key)
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    pub fn mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_just_pressed.contains(&button)
+    }
+
+    pub fn mouse_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_just_released.contains(&button)
+    }
+
+    /// physical pixels, (0, 0) at top left, none when outside the window
+    pub fn cursor_position(&self) -> Option<[f32; 2]> {
+        self.cursor
+    }
+
+    /// 0..1 across the window, (0, 0) at top left
+    pub fn cursor_normalized(&self) -> Option<[f32; 2]> {
+        let [width, height] = self.window_size;
+        if width <= 0. || height <= 0. {
+            return None;
+        }
+        self.cursor.map(|[x, y]| [x / width, y / height])
+    }
+
+    /// physical pixels moved in this frame
+    pub fn cursor_delta(&self) -> [f32; 2] {
+        self.cursor_delta
+    }
+
+    /// lines scrolled in this frame, positive y scrolls up
+    pub fn scroll_delta(&self) -> [f32; 2] {
+        self.scroll_delta
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn just_pressed_and_released() {
+        let mut input = Input::default();
+        input.press_key(KeyCode::KeyW);
+        input.press_button(MouseButton::Left);
+        assert!(input.pressed(KeyCode::KeyW) && input.just_pressed(KeyCode::KeyW));
+        assert!(input.mouse_just_pressed(MouseButton::Left));
+        input.end_frame();
+        assert!(input.pressed(KeyCode::KeyW) && !input.just_pressed(KeyCode::KeyW));
+        input.release_key(KeyCode::KeyW);
+        assert!(!input.pressed(KeyCode::KeyW) && input.just_released(KeyCode::KeyW));
+        input.release_all();
+        assert!(input.mouse_just_released(MouseButton::Left));
+        input.end_frame();
+        assert!(!input.just_released(KeyCode::KeyW));
+    }
+
+    #[test]
+    fn cursor() {
+        let mut input = Input {
+            window_size: [200., 100.],
+            ..Default::default()
+        };
+        assert_eq!(input.cursor_normalized(), None);
+        input.move_cursor([50., 50.]);
+        input.move_cursor([60., 40.]);
+        assert_eq!(input.cursor_delta(), [10., -10.]);
+        assert_eq!(input.cursor_normalized(), Some([0.3, 0.4]));
+        input.end_frame();
+        assert_eq!(input.cursor_delta(), [0., 0.]);
+    }
+}
@@ -1,10 +1,31 @@
+pub mod camera;
+pub mod debug_draw;
+pub mod events;
 pub mod gfx;
+#[cfg(feature = "winit")]
+pub mod input;
+pub mod mesh;
 pub mod multi;
+#[cfg(feature = "winit")]
+mod runner;
 pub mod scene;
+pub mod state;
+pub mod text;
+pub mod texture;
 pub mod time;
-use crate::gfx::Gfx;
-use crate::scene::{Queue, Scene};
+pub mod timing;
+#[cfg(feature = "chrome_trace")]
+pub mod trace;
+use crate::gfx::{Gfx, GfxError};
+use crate::scene::{ErrorPolicy, HashTypeId2Data, Queue, Scene, SystemError};
+use crate::timing::TimingReport;
+#[cfg(feature = "winit")]
+pub use runner::run;
+use std::any::{Any, TypeId};
+#[cfg(feature = "winit")]
+pub use winit;
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Default)]
 pub enum RenderEntry {
     #[default]
@@ -12,10 +33,21 @@ pub enum RenderEntry {
     Ready(Gfx),
 }
 
-#[derive(Default)]
 pub struct Render {
     pub entry: RenderEntry,
     pub scenes: Vec<Scene>,
+    /// resources shared by every scene, see `Render::insert_global`
+    globals: HashTypeId2Data,
+    /// `Render::ready` ran, scenes added later are readied right away
+    readied: bool,
+    error_policy: ErrorPolicy,
+    system_timing: bool,
+}
+
+impl Default for Render {
+    fn default() -> Self {
+        Render::new()
+    }
 }
 
 impl Render {
@@ -23,41 +55,345 @@ impl Render {
         Render {
             entry: RenderEntry::NotReady,
             scenes: Vec::new(),
+            globals: HashTypeId2Data::new(),
+            readied: false,
+            error_policy: ErrorPolicy::default(),
+            system_timing: false,
         }
     }
 
-    pub fn ready(&mut self) {
-        println!("Render::ready");
+    /// resource visible to the systems of every scene through `get_res` and friends,
+    /// a scene resource of the same type shadows it in that scene.
+    /// insert them before `Render::ready` so the readys can use them
+    pub fn insert_global<T: Any>(&mut self, res: T) -> &mut Self {
+        self.globals.insert(TypeId::of::<T>(), Box::new(res));
+        self
+    }
+
+    pub fn global<T: Any>(&self) -> Option<&T> {
+        self.globals
+            .get(&TypeId::of::<T>())
+            .and_then(|res| res.downcast_ref::<T>())
+    }
+
+    pub fn global_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.globals
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|res| res.downcast_mut::<T>())
+    }
+
+    pub fn remove_global<T: Any>(&mut self) -> Option<T> {
+        self.globals
+            .remove(&TypeId::of::<T>())
+            .and_then(|res| res.downcast::<T>().ok())
+            .map(|res| *res)
+    }
+
+    pub fn gfx(&self) -> Option<&Gfx> {
         match &self.entry {
-            RenderEntry::Ready(ref gfx) => {
-                for scene in self.scenes.iter_mut() {
-                    scene.ready(gfx);
-                }
-            }
-            _ => panic!("Render::get_gfx called before gfx is ready"),
+            RenderEntry::Ready(gfx) => Some(gfx),
+            RenderEntry::NotReady => None,
+        }
+    }
+
+    pub fn gfx_mut(&mut self) -> Option<&mut Gfx> {
+        match &mut self.entry {
+            RenderEntry::Ready(gfx) => Some(gfx),
+            RenderEntry::NotReady => None,
+        }
+    }
+
+    /// how every scene handles a failing system, `ErrorPolicy::Panic` by default.
+    /// unless it panics, `Render::ready` and `Render::paint` called before gfx is ready are skipped
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) -> &mut Self {
+        self.error_policy = policy;
+        for scene in self.scenes.iter_mut() {
+            scene.set_error_policy(policy);
+        }
+        self
+    }
+
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    /// the last errors of every scene, `Render::scene(name)` for one scene
+    pub fn errors(&self) -> impl Iterator<Item = &SystemError> {
+        self.scenes.iter().flat_map(|scene| scene.errors())
+    }
+
+    /// opt in cpu timing of every ready, fixed update and paint of every scene, off by default
+    pub fn set_system_timing(&mut self, enabled: bool) -> &mut Self {
+        self.system_timing = enabled;
+        for scene in self.scenes.iter_mut() {
+            scene.set_system_timing(enabled);
+        }
+        self
+    }
+
+    /// rolling average and worst cpu time per system of every scene, print it for a text table
+    pub fn timing_report(&self) -> TimingReport {
+        TimingReport {
+            entries: self
+                .scenes
+                .iter()
+                .flat_map(|scene| scene.timing_report().entries)
+                .collect(),
+        }
+    }
+
+    /// the timing table as a `tracing` info event, e.g. on a key press
+    pub fn log_timing_report(&self) {
+        tracing::info!("system timings\n{}", self.timing_report());
+    }
+
+    fn not_ready(&self, caller: &str) {
+        match self.error_policy {
+            ErrorPolicy::Panic => panic!("{} called before gfx is ready", caller),
+            _ => tracing::warn!("{} called before gfx is ready, skipped", caller),
+        }
+    }
+
+    pub fn ready(&mut self) {
+        let _span = tracing::info_span!("Render::ready").entered();
+        let RenderEntry::Ready(ref gfx) = self.entry else {
+            return self.not_ready("Render::ready");
+        };
+        self.readied = true;
+        for scene in self.scenes.iter_mut() {
+            with_globals(&mut self.globals, scene, |scene| scene.ready(gfx));
         }
     }
 
     pub fn paint(&mut self) {
+        let _span = tracing::debug_span!("Render::paint").entered();
+        if let RenderEntry::Ready(ref mut gfx) = self.entry {
+            gfx.time.tick();
+            if gfx.time.frame_count() > 1 {
+                gfx.fps_history.push(gfx.time.real_delta());
+            }
+        }
+        self.paint_scenes();
+        #[cfg(feature = "winit")]
+        if let RenderEntry::Ready(ref mut gfx) = self.entry {
+            gfx.input.end_frame();
+        }
+    }
+
+    fn paint_scenes(&mut self) {
         match &self.entry {
             RenderEntry::Ready(ref gfx) => {
+                if gfx.is_device_lost() {
+                    return;
+                }
+                gfx.begin_profile();
+                for scene in self.scenes.iter_mut().filter(|s| s.is_enabled()) {
+                    with_globals(&mut self.globals, scene, |scene| scene.compute(gfx));
+                }
                 for scene in self.scenes.iter_mut() {
-                    scene.paint(gfx);
+                    if scene.is_enabled() {
+                        with_globals(&mut self.globals, scene, |scene| scene.paint(gfx));
+                    } else {
+                        scene.clear_overlays();
+                    }
                 }
+                gfx.end_profile();
+                gfx.recall_staging();
             }
-            _ => panic!("Render::get_gfx called before gfx is ready"),
+            _ => self.not_ready("Render::paint"),
         }
     }
 
-    pub fn add_scene<T: Queue>(&mut self, name: impl Into<String> + Clone) -> &mut Self {
-        println!("add_scene: {}", name.clone().into());
+    /// feed a window event to `gfx.input`, then to the `OnEvent` handlers of each scene in order
+    /// until one consumes it, `ready_paint::run` call it for every event.
+    /// true when it was consumed, `gfx.input` sees every event anyway
+    #[cfg(feature = "winit")]
+    pub fn handle_event(&mut self, event: &winit::event::WindowEvent) -> bool {
+        let RenderEntry::Ready(ref mut gfx) = self.entry else {
+            return false;
+        };
+        gfx.input.handle_event(event);
+        let gfx = &*gfx;
+        let globals = &mut self.globals;
+        self.scenes
+            .iter_mut()
+            .filter(|s| s.is_enabled())
+            .any(|scene| with_globals(globals, scene, |scene| scene.handle_event(gfx, event)))
+    }
+
+    /// true once the gpu device is lost, `Render::paint` does nothing until `Render::recover`
+    pub fn is_device_lost(&self) -> bool {
+        match &self.entry {
+            RenderEntry::Ready(ref gfx) => gfx.is_device_lost(),
+            _ => false,
+        }
+    }
+
+    /// recreate the gpu state on window and run every scene's readys again in order,
+    /// on error the gfx is gone (`RenderEntry::NotReady`) and the next call returns `GfxError::NotReady`
+    pub async fn recover(
+        &mut self,
+        window: impl Into<wgpu::SurfaceTarget<'static>>,
+    ) -> Result<(), GfxError> {
+        let _span = tracing::info_span!("Render::recover").entered();
+        let RenderEntry::Ready(gfx) = std::mem::take(&mut self.entry) else {
+            return Err(GfxError::NotReady);
+        };
+        self.recovered(gfx.recover(window).await?);
+        Ok(())
+    }
+
+    /// the scene half of `Render::recover`, for a gfx recovered without borrowing the render
+    /// (`ready_paint::run` on the web, where it can't block on `Gfx::recover`)
+    pub(crate) fn recovered(&mut self, gfx: Gfx) {
+        self.entry = RenderEntry::Ready(gfx);
+        let RenderEntry::Ready(ref gfx) = self.entry else {
+            unreachable!();
+        };
+        for scene in self.scenes.iter_mut() {
+            with_globals(&mut self.globals, scene, |scene| scene.recover(gfx));
+        }
+    }
+
+    /// a scene added after `Render::ready` is readied right away
+    pub fn add_scene<T: Queue>(&mut self, name: impl Into<String>) -> &mut Self {
         let mut s = Scene::new(name.into());
+        tracing::debug!(scene = s.get_name(), "Render::add_scene");
+        s.set_error_policy(self.error_policy);
+        s.set_system_timing(self.system_timing);
         T::introduce(&mut s);
+        if let (true, RenderEntry::Ready(ref gfx)) = (self.readied, &self.entry) {
+            with_globals(&mut self.globals, &mut s, |scene| scene.ready(gfx));
+        }
         self.scenes.push(s);
         self
     }
+
+    /// first scene with this name
+    pub fn scene(&self, name: &str) -> Option<&Scene> {
+        self.scenes.iter().find(|s| s.get_name() == name)
+    }
+
+    pub fn scene_mut(&mut self, name: &str) -> Option<&mut Scene> {
+        self.scenes.iter_mut().find(|s| s.get_name() == name)
+    }
+
+    fn scene_index(&self, name: &str) -> Option<usize> {
+        self.scenes.iter().position(|s| s.get_name() == name)
+    }
+
+    pub fn remove_scene(&mut self, name: &str) -> Option<Scene> {
+        let index = self.scene_index(name)?;
+        Some(self.scenes.remove(index))
+    }
+
+    /// paint and draw `name` right before `before`, false when either is missing
+    pub fn move_scene_before(&mut self, name: &str, before: &str) -> bool {
+        self.move_scene(name, before, 0)
+    }
+
+    /// paint and draw `name` right after `after`, false when either is missing
+    pub fn move_scene_after(&mut self, name: &str, after: &str) -> bool {
+        self.move_scene(name, after, 1)
+    }
+
+    fn move_scene(&mut self, name: &str, target: &str, offset: usize) -> bool {
+        if self.scene_index(target).is_none() {
+            return false;
+        }
+        if name == target {
+            return true;
+        }
+        let Some(scene) = self.remove_scene(name) else {
+            return false;
+        };
+        let index = self.scene_index(target).unwrap() + offset;
+        self.scenes.insert(index, scene);
+        true
+    }
+
+    /// a disabled scene keeps its resources but doesn't paint, draw or get events.
+    /// enabling a scene disabled by failed readys runs them again first, it stays disabled
+    /// if they fail again. true when the scene is now in the requested state
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let Some(index) = self.scene_index(name) else {
+            return false;
+        };
+        let scene = &mut self.scenes[index];
+        if enabled && scene.failed_readys().next().is_some() {
+            // without gfx (a failed recover) the readys can't run and the scene stays disabled
+            if let RenderEntry::Ready(ref gfx) = self.entry {
+                with_globals(&mut self.globals, scene, |scene| scene.retry_readys(gfx));
+            }
+        }
+        scene.set_enabled(enabled);
+        scene.is_enabled() == enabled
+    }
+}
+
+/// lend the globals the scene doesn't shadow for the duration of f
+fn with_globals<R>(
+    globals: &mut HashTypeId2Data,
+    scene: &mut Scene,
+    f: impl FnOnce(&mut Scene) -> R,
+) -> R {
+    let lent = scene.lend_globals(globals);
+    let result = f(scene);
+    scene.return_globals(globals, lent);
+    result
 }
 
 pub mod prelude {
-    pub use crate::scene::{HashTypeId2Data, Paint, Queue, Ready, Scene};
+    #[cfg(feature = "winit")]
+    pub use crate::scene::OnEvent;
+    pub use crate::scene::{
+        Compute, HashTypeId2Data, Overlay, Paint, Pass, Queue, Ready, Recover, Scene, TryPaint,
+        TryReady,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Empty;
+
+    impl Queue for Empty {
+        fn introduce(_scene: &mut Scene) {}
+    }
+
+    fn names(render: &Render) -> Vec<&str> {
+        render.scenes.iter().map(|s| s.get_name()).collect()
+    }
+
+    #[test]
+    fn scene_order() {
+        let mut render = Render::new();
+        for name in ["a", "b", "c", "d"] {
+            render.add_scene::<Empty>(name);
+        }
+        assert!(render.move_scene_after("a", "c"));
+        assert_eq!(names(&render), ["b", "c", "a", "d"]);
+        assert!(render.move_scene_before("d", "b"));
+        assert_eq!(names(&render), ["d", "b", "c", "a"]);
+        assert!(render.move_scene_after("a", "a"));
+        assert!(render.move_scene_before("b", "b"));
+        assert_eq!(names(&render), ["d", "b", "c", "a"]);
+        assert!(!render.move_scene_before("e", "b"));
+        assert!(!render.move_scene_before("b", "e"));
+        assert!(!render.move_scene_after("e", "e"));
+
+        assert_eq!(render.remove_scene("b").unwrap().get_name(), "b");
+        assert!(render.remove_scene("b").is_none());
+        assert_eq!(names(&render), ["d", "c", "a"]);
+
+        assert!(render.set_enabled("c", false));
+        assert!(!render.set_enabled("b", false));
+        assert!(!render.scene("c").unwrap().is_enabled());
+        // disabling keeps the scene in place
+        assert!(render.move_scene_after("d", "a"));
+        assert_eq!(names(&render), ["c", "a", "d"]);
+        assert!(render.set_enabled("c", true));
+        assert!(render.scene("c").unwrap().is_enabled());
+    }
 }
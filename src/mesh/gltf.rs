@@ -0,0 +1,138 @@
+use super::{Material, Mesh, Model, Vertex};
+use glam::{Mat3, Mat4, Vec3};
+use std::{error::Error, path::Path};
+
+impl Model {
+    /// load a glTF 2.0 file (.gltf with its buffers, or binary .glb) from local disk,
+    /// node transforms of the default scene are baked into the vertices
+    pub fn load_gltf(path: impl AsRef<Path>) -> Result<Model, Box<dyn Error>> {
+        let (document, buffers, _images) = gltf::import(path)?;
+        Self::from_gltf(&document, &buffers)
+    }
+
+    /// same as `load_gltf` but from the bytes of a .glb or a self-contained .gltf
+    pub fn load_gltf_slice(bytes: &[u8]) -> Result<Model, Box<dyn Error>> {
+        let (document, buffers, _images) = gltf::import_slice(bytes)?;
+        Self::from_gltf(&document, &buffers)
+    }
+
+    fn from_gltf(
+        document: &gltf::Document,
+        buffers: &[gltf::buffer::Data],
+    ) -> Result<Model, Box<dyn Error>> {
+        let materials = document
+            .materials()
+            .map(|m| Material {
+                name: m.name().unwrap_or_default().to_string(),
+                base_color: m.pbr_metallic_roughness().base_color_factor(),
+            })
+            .collect();
+        let mut model = Model {
+            meshes: Vec::new(),
+            materials,
+        };
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or("glTF file has no scene")?;
+        for node in scene.nodes() {
+            collect_node(&node, Mat4::IDENTITY, buffers, &mut model.meshes);
+        }
+        Ok(model)
+    }
+}
+
+fn collect_node(
+    node: &gltf::Node,
+    parent: Mat4,
+    buffers: &[gltf::buffer::Data],
+    meshes: &mut Vec<Mesh>,
+) {
+    let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+    // inverse transpose keeps normals perpendicular under non-uniform scale
+    let normal_matrix = Mat3::from_mat4(transform).inverse().transpose();
+    if let Some(gltf_mesh) = node.mesh() {
+        for primitive in gltf_mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let mut vertices: Vec<Vertex> = positions
+                .map(|p| Vertex {
+                    position: transform.transform_point3(Vec3::from(p)).into(),
+                    ..Default::default()
+                })
+                .collect();
+            let has_normals = if let Some(normals) = reader.read_normals() {
+                for (vertex, n) in vertices.iter_mut().zip(normals) {
+                    vertex.normal = (normal_matrix * Vec3::from(n))
+                        .normalize_or(Vec3::from(n))
+                        .into();
+                }
+                true
+            } else {
+                false
+            };
+            if let Some(uvs) = reader.read_tex_coords(0) {
+                for (vertex, uv) in vertices.iter_mut().zip(uvs.into_f32()) {
+                    vertex.uv = uv;
+                }
+            }
+            let indices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertices.len() as u32).collect(),
+            };
+            let mut mesh = Mesh::new(vertices, indices);
+            mesh.material = primitive.material().index();
+            if !has_normals {
+                mesh.compute_normals();
+            }
+            meshes.push(mesh);
+        }
+    }
+    for child in node.children() {
+        collect_node(&child, transform, buffers, meshes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// one triangle in the x + y = 0 plane under a node scaled 2x along x
+    const SCALED_TRIANGLE: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0, "scale": [2, 1, 1] }],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0, "NORMAL": 1 } }] }],
+        "buffers": [{
+            "byteLength": 72,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAIA/AACAPwAAgL8AAAAA8wQ1P/MENT8AAAAA8wQ1P/MENT8AAAAA8wQ1P/MENT8AAAAA"
+        }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 36 }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+              "min": [0, -1, 0], "max": [1, 0, 1] },
+            { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" }
+        ]
+    }"#;
+
+    #[test]
+    fn non_uniform_scale() {
+        let model = Model::load_gltf_slice(SCALED_TRIANGLE.as_bytes()).unwrap();
+        let mesh = &model.meshes[0];
+        assert_eq!(mesh.indices, [0, 1, 2]);
+        assert_eq!(mesh.vertices[2].position, [2., -1., 0.]);
+        let expected = Vec3::new(1., 2., 0.).normalize();
+        for vertex in mesh.vertices.iter() {
+            assert!(Vec3::from(vertex.normal).distance(expected) < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::gfx::Gfx;
+use std::collections::HashSet;
+use wgpu::util::DeviceExt;
+
+#[cfg(feature = "gltf")]
+mod gltf;
+mod model;
+#[cfg(feature = "obj")]
+mod obj;
+pub mod primitive;
+
+use primitive::{add, cross, dot, normalize, sub};
+
+pub use model::{Material, Model};
+
+/// position, normal and uv, laid out for `@location(0..3)` in the shader
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl Vertex {
+    pub const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2];
+
+    pub fn new(position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) -> Self {
+        Vertex {
+            position,
+            normal,
+            uv,
+        }
+    }
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Mesh keep the cpu side vertex/index data,
+/// after `upload` it also hold the gpu buffers and can draw itself into a RenderPass.
+/// indices are a triangle list, `line_indices` (optional) are a line list for wireframe.
+#[derive(Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub line_indices: Vec<u32>,
+    /// index into `Model::materials` when loaded from a model file
+    pub material: Option<usize>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    index_buffer: Option<wgpu::Buffer>,
+    line_index_buffer: Option<wgpu::Buffer>,
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        Mesh {
+            vertices,
+            indices,
+            ..Default::default()
+        }
+    }
+
+    /// generate `line_indices` from the triangle indices
+    pub fn with_wireframe(mut self) -> Self {
+        self.line_indices = wireframe_indices(&self.indices);
+        self
+    }
+
+    /// smooth normals averaged from the triangles sharing each vertex
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![[0f32; 3]; self.vertices.len()];
+        for tri in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertices[tri[i] as usize].position);
+            let n = cross(sub(b, a), sub(c, a));
+            for &i in tri {
+                normals[i as usize] = add(normals[i as usize], n);
+            }
+        }
+        for (vertex, n) in self.vertices.iter_mut().zip(normals) {
+            if dot(n, n) > 0. {
+                vertex.normal = normalize(n);
+            }
+        }
+    }
+
+    /// create (or recreate) the gpu buffers from the current cpu data
+    pub fn upload(&mut self, gfx: &Gfx) {
+        self.vertex_buffer = Some(gfx.device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Mesh Vertex Buffer"),
+                contents: bytemuck::cast_slice(&self.vertices),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            },
+        ));
+        self.index_buffer = Some(gfx.device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Mesh Index Buffer"),
+                contents: bytemuck::cast_slice(&self.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            },
+        ));
+        self.line_index_buffer = (!self.line_indices.is_empty()).then(|| {
+            gfx.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Mesh Line Index Buffer"),
+                    contents: bytemuck::cast_slice(&self.line_indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                })
+        });
+    }
+
+    pub fn is_uploaded(&self) -> bool {
+        self.vertex_buffer.is_some()
+    }
+
+    pub fn vertex_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.vertex_buffer.as_ref()
+    }
+
+    pub fn index_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.index_buffer.as_ref()
+    }
+
+    /// draw as triangle list, the pipeline set on render_pass should use `Vertex::layout`
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.draw_instanced(render_pass, 0..1);
+    }
+
+    pub fn draw_instanced(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        instances: std::ops::Range<u32>,
+    ) {
+        let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer)
+        else {
+            panic!("Mesh::draw called before Mesh::upload");
+        };
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.indices.len() as u32, 0, instances);
+    }
+
+    /// draw as line list, the pipeline set on render_pass should use `PrimitiveTopology::LineList`
+    pub fn draw_wireframe(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        let (Some(vertex_buffer), Some(line_index_buffer)) =
+            (&self.vertex_buffer, &self.line_index_buffer)
+        else {
+            panic!("Mesh::draw_wireframe called without uploaded line indices, use Mesh::with_wireframe");
+        };
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(line_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.line_indices.len() as u32, 0, 0..1);
+    }
+}
+
+/// turn triangle list indices into line list indices, shared edges only appear once
+pub fn wireframe_indices(triangles: &[u32]) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    for tri in triangles.chunks_exact(3) {
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            if seen.insert((a.min(b), a.max(b))) {
+                lines.push(a);
+                lines.push(b);
+            }
+        }
+    }
+    lines
+}
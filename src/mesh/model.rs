@@ -0,0 +1,54 @@
+use super::Mesh;
+use crate::gfx::Gfx;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// linear rgba
+    pub base_color: [f32; 4],
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: String::new(),
+            base_color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+}
+
+/// meshes and materials loaded from a model file,
+/// load it in your `Ready` and keep it as a scene resource:
+/// ```ignore
+/// impl Ready for Teapot {
+///     fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+///         let mut model = Model::load_obj("assets/teapot.obj").unwrap();
+///         model.upload(gfx);
+///         return_res(data, Teapot { model: Some(model) });
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct Model {
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<Material>,
+}
+
+impl Model {
+    pub fn upload(&mut self, gfx: &Gfx) {
+        for mesh in self.meshes.iter_mut() {
+            mesh.upload(gfx);
+        }
+    }
+
+    pub fn material(&self, mesh: &Mesh) -> Option<&Material> {
+        mesh.material.and_then(|i| self.materials.get(i))
+    }
+
+    /// draw every mesh, bind per-material data yourself if needed by iterating `meshes`
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        for mesh in self.meshes.iter() {
+            mesh.draw(render_pass);
+        }
+    }
+}
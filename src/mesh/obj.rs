@@ -0,0 +1,85 @@
+use super::{Material, Mesh, Model, Vertex};
+use std::{error::Error, path::Path};
+
+impl Model {
+    /// load a Wavefront OBJ (and its .mtl if found) from local disk,
+    /// faces are triangulated and missing normals are computed
+    pub fn load_obj(path: impl AsRef<Path>) -> Result<Model, Box<dyn Error>> {
+        let (models, materials) = tobj::load_obj(path.as_ref(), &tobj::GPU_LOAD_OPTIONS)?;
+        Ok(Self::from_tobj(models, materials))
+    }
+
+    fn from_tobj(
+        models: Vec<tobj::Model>,
+        materials: Result<Vec<tobj::Material>, tobj::LoadError>,
+    ) -> Model {
+        let materials = materials
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| {
+                let [r, g, b] = m.diffuse.unwrap_or([1.0, 1.0, 1.0]);
+                Material {
+                    name: m.name,
+                    base_color: [r, g, b, m.dissolve.unwrap_or(1.0)],
+                }
+            })
+            .collect();
+        let meshes = models
+            .into_iter()
+            .map(|model| {
+                let m = model.mesh;
+                let has_normals = !m.normals.is_empty();
+                let vertices = (0..m.positions.len() / 3)
+                    .map(|i| Vertex {
+                        position: [
+                            m.positions[i * 3],
+                            m.positions[i * 3 + 1],
+                            m.positions[i * 3 + 2],
+                        ],
+                        normal: if has_normals {
+                            [m.normals[i * 3], m.normals[i * 3 + 1], m.normals[i * 3 + 2]]
+                        } else {
+                            [0.0; 3]
+                        },
+                        // obj v goes up, wgpu texture v goes down
+                        uv: if m.texcoords.is_empty() {
+                            [0.0; 2]
+                        } else {
+                            [m.texcoords[i * 2], 1.0 - m.texcoords[i * 2 + 1]]
+                        },
+                    })
+                    .collect();
+                let mut mesh = Mesh::new(vertices, m.indices);
+                mesh.material = m.material_id;
+                if !has_normals {
+                    mesh.compute_normals();
+                }
+                mesh
+            })
+            .collect();
+        Model { meshes, materials }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quad_without_normals() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nf 1/1 2/2 3/3 4/4\n";
+        let (models, materials) = tobj::load_obj_buf(
+            &mut std::io::Cursor::new(source),
+            &tobj::GPU_LOAD_OPTIONS,
+            |_| Err(tobj::LoadError::OpenFileFailed),
+        )
+        .unwrap();
+        let model = Model::from_tobj(models, materials);
+        let mesh = &model.meshes[0];
+        assert_eq!(mesh.indices.len(), 6);
+        assert_eq!(mesh.vertices.len(), 4);
+        assert!(mesh.vertices.iter().all(|v| v.normal == [0., 0., 1.]));
+        assert_eq!(mesh.vertices[0].uv, [0., 1.]);
+        assert!(model.materials.is_empty());
+    }
+}
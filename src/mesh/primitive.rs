@@ -0,0 +1,283 @@
+use super::{Mesh, Vertex};
+use std::f32::consts::{PI, TAU};
+
+/// built-in shapes, all centered at origin, y up, counter-clockwise front faces
+impl Mesh {
+    /// quad on the XY plane facing +Z
+    pub fn quad(width: f32, height: f32) -> Self {
+        let mut mesh = Mesh::default();
+        push_face(
+            &mut mesh,
+            [0., 0., 0.],
+            [width / 2., 0., 0.],
+            [0., height / 2., 0.],
+            [0., 0., 1.],
+        );
+        mesh
+    }
+
+    pub fn cube(size: f32) -> Self {
+        let h = size / 2.;
+        let mut mesh = Mesh::default();
+        // (normal, u axis, v axis), u x v = normal
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1., 0., 0.], [0., 0., -1.], [0., 1., 0.]),
+            ([-1., 0., 0.], [0., 0., 1.], [0., 1., 0.]),
+            ([0., 1., 0.], [1., 0., 0.], [0., 0., -1.]),
+            ([0., -1., 0.], [1., 0., 0.], [0., 0., 1.]),
+            ([0., 0., 1.], [1., 0., 0.], [0., 1., 0.]),
+            ([0., 0., -1.], [-1., 0., 0.], [0., 1., 0.]),
+        ];
+        for (n, u, v) in faces {
+            push_face(&mut mesh, scale(n, h), scale(u, h), scale(v, h), n);
+        }
+        mesh
+    }
+
+    /// uv sphere, `segments` around the y axis and `rings` from top to bottom
+    pub fn sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let segments = segments.max(3);
+        let rings = rings.max(2);
+        let mut mesh = Mesh::default();
+        for r in 0..=rings {
+            let phi = PI * r as f32 / rings as f32;
+            for s in 0..=segments {
+                let theta = TAU * s as f32 / segments as f32;
+                let n = [phi.sin() * theta.cos(), phi.cos(), -phi.sin() * theta.sin()];
+                mesh.vertices.push(Vertex::new(
+                    scale(n, radius),
+                    n,
+                    [s as f32 / segments as f32, r as f32 / rings as f32],
+                ));
+            }
+        }
+        let row = segments + 1;
+        for r in 0..rings {
+            for s in 0..segments {
+                let (a, b, c, d) = (
+                    r * row + s,
+                    (r + 1) * row + s,
+                    (r + 1) * row + s + 1,
+                    r * row + s + 1,
+                );
+                // skip the degenerate triangles touching the poles
+                if r != rings - 1 {
+                    mesh.indices.extend([a, b, c]);
+                }
+                if r != 0 {
+                    mesh.indices.extend([a, c, d]);
+                }
+            }
+        }
+        mesh
+    }
+
+    /// cylinder along the y axis with top and bottom caps
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        let segments = segments.max(3);
+        let h = height / 2.;
+        let mut mesh = Mesh::default();
+        let ring = |s: u32| {
+            let theta = TAU * s as f32 / segments as f32;
+            (theta.cos(), -theta.sin())
+        };
+        // side
+        for (y, v) in [(h, 0.), (-h, 1.)] {
+            for s in 0..=segments {
+                let (x, z) = ring(s);
+                mesh.vertices.push(Vertex::new(
+                    [x * radius, y, z * radius],
+                    [x, 0., z],
+                    [s as f32 / segments as f32, v],
+                ));
+            }
+        }
+        let row = segments + 1;
+        for s in 0..segments {
+            let (a, b, c, d) = (s, row + s, row + s + 1, s + 1);
+            mesh.indices.extend([a, b, c, a, c, d]);
+        }
+        // caps
+        for (y, ny) in [(h, 1.), (-h, -1.)] {
+            let center = mesh.vertices.len() as u32;
+            mesh.vertices
+                .push(Vertex::new([0., y, 0.], [0., ny, 0.], [0.5, 0.5]));
+            for s in 0..=segments {
+                let (x, z) = ring(s);
+                mesh.vertices.push(Vertex::new(
+                    [x * radius, y, z * radius],
+                    [0., ny, 0.],
+                    [0.5 + x / 2., 0.5 + z / 2.],
+                ));
+            }
+            for s in 0..segments {
+                let (p0, p1) = (center + 1 + s, center + 2 + s);
+                if ny > 0. {
+                    mesh.indices.extend([center, p0, p1]);
+                } else {
+                    mesh.indices.extend([center, p1, p0]);
+                }
+            }
+        }
+        mesh
+    }
+
+    /// grid on the XZ plane facing +Y, split into `subdivisions_x` * `subdivisions_z` cells
+    pub fn plane(width: f32, depth: f32, subdivisions_x: u32, subdivisions_z: u32) -> Self {
+        let (nx, nz) = (subdivisions_x.max(1), subdivisions_z.max(1));
+        let mut mesh = Mesh::default();
+        for i in 0..=nz {
+            let v = i as f32 / nz as f32;
+            for j in 0..=nx {
+                let u = j as f32 / nx as f32;
+                mesh.vertices.push(Vertex::new(
+                    [(u - 0.5) * width, 0., (v - 0.5) * depth],
+                    [0., 1., 0.],
+                    [u, v],
+                ));
+            }
+        }
+        let row = nx + 1;
+        for i in 0..nz {
+            for j in 0..nx {
+                let (a, b, c, d) = (
+                    i * row + j,
+                    (i + 1) * row + j,
+                    (i + 1) * row + j + 1,
+                    i * row + j + 1,
+                );
+                mesh.indices.extend([a, b, c, a, c, d]);
+            }
+        }
+        mesh
+    }
+
+    /// regular tetrahedron with flat normals, `size` is the distance from center to a corner
+    pub fn tetrahedron(size: f32) -> Self {
+        let k = size / 3f32.sqrt();
+        let corners = [[k, k, k], [k, -k, -k], [-k, k, -k], [-k, -k, k]];
+        let mut mesh = Mesh::default();
+        for [i, j, l] in [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]] {
+            let (mut a, b, mut c) = (corners[i], corners[j], corners[l]);
+            let mut n = normalize(cross(sub(b, a), sub(c, a)));
+            // corners average of the face point away from center, flip if normal doesn't
+            if dot(n, add(add(a, b), c)) < 0. {
+                std::mem::swap(&mut a, &mut c);
+                n = scale(n, -1.);
+            }
+            let base = mesh.vertices.len() as u32;
+            mesh.vertices.extend([
+                Vertex::new(a, n, [0.5, 0.]),
+                Vertex::new(b, n, [0., 1.]),
+                Vertex::new(c, n, [1., 1.]),
+            ]);
+            mesh.indices.extend([base, base + 1, base + 2]);
+        }
+        mesh
+    }
+}
+
+/// four corners `center -/+ u -/+ v`, u x v must point to `normal`
+fn push_face(mesh: &mut Mesh, center: [f32; 3], u: [f32; 3], v: [f32; 3], normal: [f32; 3]) {
+    let base = mesh.vertices.len() as u32;
+    mesh.vertices.extend([
+        Vertex::new(sub(sub(center, u), v), normal, [0., 1.]),
+        Vertex::new(sub(add(center, u), v), normal, [1., 1.]),
+        Vertex::new(add(add(center, u), v), normal, [1., 0.]),
+        Vertex::new(add(sub(center, u), v), normal, [0., 0.]),
+    ]);
+    mesh.indices
+        .extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+}
+
+pub(super) fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+pub(super) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+pub(super) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+pub(super) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+pub(super) fn normalize(a: [f32; 3]) -> [f32; 3] {
+    scale(a, 1. / dot(a, a).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// every triangle of a closed convex mesh around origin must face outward
+    fn assert_outward(mesh: &Mesh) {
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[tri[i] as usize].position);
+            let n = cross(sub(b, a), sub(c, a));
+            assert!(dot(n, add(add(a, b), c)) > 0., "inward triangle {:?}", tri);
+        }
+    }
+
+    fn assert_valid(mesh: &Mesh) {
+        assert_eq!(mesh.indices.len() % 3, 0);
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|&i| (i as usize) < mesh.vertices.len()));
+        for v in mesh.vertices.iter() {
+            assert!((dot(v.normal, v.normal) - 1.).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        let quad = Mesh::quad(2., 1.);
+        assert_valid(&quad);
+        assert_eq!((quad.vertices.len(), quad.indices.len()), (4, 6));
+
+        let cube = Mesh::cube(1.);
+        assert_valid(&cube);
+        assert_outward(&cube);
+        assert_eq!((cube.vertices.len(), cube.indices.len()), (24, 36));
+
+        let sphere = Mesh::sphere(1., 16, 8);
+        assert_valid(&sphere);
+        assert_outward(&sphere);
+        assert_eq!(sphere.indices.len(), (16 * 8 * 2 - 2 * 16) * 3);
+
+        let cylinder = Mesh::cylinder(1., 2., 12);
+        assert_valid(&cylinder);
+        assert_outward(&cylinder);
+
+        let plane = Mesh::plane(4., 4., 4, 2);
+        assert_valid(&plane);
+        assert_eq!((plane.vertices.len(), plane.indices.len()), (15, 48));
+
+        let tetra = Mesh::tetrahedron(1.);
+        assert_valid(&tetra);
+        assert_outward(&tetra);
+        assert_eq!((tetra.vertices.len(), tetra.indices.len()), (12, 12));
+    }
+
+    #[test]
+    fn wireframe() {
+        // two triangles of the quad share the diagonal
+        assert_eq!(
+            Mesh::quad(1., 1.).with_wireframe().line_indices.len(),
+            5 * 2
+        );
+        // flat faces don't share vertices
+        assert_eq!(
+            Mesh::tetrahedron(1.).with_wireframe().line_indices.len(),
+            12 * 2
+        );
+    }
+}
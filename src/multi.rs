@@ -1,15 +1,17 @@
 use std::{any::TypeId, marker::PhantomData};
 
-use crate::scene::{HashTypeId2Data, Ready};
+use crate::scene::HashTypeId2Data;
 pub trait InRefOrMut {
     type AccessMode;
     type Output;
 }
 /// # Safety
 /// 1. T1 and T2 .. T4 must be different types from each.
-/// 2. T1 and T2 .. T4 must be wrap in Ref<T> Or Mut<T>, T must be impl `Ready`.
+/// 2. T1 and T2 .. T4 must be wrap in Ref<T> Or Mut<T>, T is any resource in data:
+///    a `Ready` system, or a global lent to the scene like `Input`, `Events<T>` or `FixedTime`.
+///
 /// example:
-/// ```
+/// ```ignore
 /// refs_muts::<(Ref<Data1>, Mut<Data2>)>(&mut data);
 /// ```
 pub fn refs_muts<T: TurpleAccess>(data: &mut HashTypeId2Data) -> T::Output<'_> {
@@ -29,7 +31,7 @@ pub trait RefOrMut {
     fn process<'a>(data: &'a mut HashTypeId2Data) -> Self::Output<'a>;
 }
 pub struct Ref<T>(PhantomData<T>);
-impl<T: 'static + Ready> RefOrMut for Ref<T> {
+impl<T: 'static> RefOrMut for Ref<T> {
     type Target = T;
     type Mode = Read;
     type Output<'a> = &'a T;
@@ -37,15 +39,17 @@ impl<T: 'static + Ready> RefOrMut for Ref<T> {
     fn process<'a>(data: &'a mut HashTypeId2Data) -> Self::Output<'a> {
         data.get(&TypeId::of::<T>())
             .and_then(|r| r.downcast_ref::<T>())
-            .expect(&format!(
-                "Failed to get resource of type: {}",
-                std::any::type_name::<T>()
-            ))
+            .unwrap_or_else(|| {
+                panic!(
+                    "Failed to get resource of type: {}",
+                    std::any::type_name::<T>()
+                )
+            })
     }
 }
 
 pub struct Mut<T>(PhantomData<T>);
-impl<T: 'static + Ready> RefOrMut for Mut<T> {
+impl<T: 'static> RefOrMut for Mut<T> {
     type Target = T;
     type Mode = Write;
     type Output<'a> = &'a mut T;
@@ -53,10 +57,12 @@ impl<T: 'static + Ready> RefOrMut for Mut<T> {
     fn process<'a>(data: &'a mut HashTypeId2Data) -> Self::Output<'a> {
         data.get_mut(&TypeId::of::<T>())
             .and_then(|m| m.downcast_mut::<T>())
-            .expect(&format!(
-                "Failed to get mutable resource of type: {}",
-                std::any::type_name::<T>()
-            ))
+            .unwrap_or_else(|| {
+                panic!(
+                    "Failed to get mutable resource of type: {}",
+                    std::any::type_name::<T>()
+                )
+            })
     }
 }
 pub trait TurpleAccess {
@@ -0,0 +1,201 @@
+use crate::{
+    gfx::{Gfx, GfxError},
+    Render, RenderEntry,
+};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy},
+    window::{Window, WindowAttributes, WindowId},
+};
+
+/// open a window and drive `Render` until it's closed:
+/// build `Gfx` on the window, call `render_builder` to add scenes (and tweak `Render::gfx_mut`),
+/// `Render::ready` once the surface is configured, then pace and paint every frame.
+/// ```ignore
+/// ready_paint::run(
+///     |render| {
+///         render.add_scene::<MyScene>("main");
+///     },
+///     Window::default_attributes().with_title("my app"),
+/// );
+/// ```
+/// on the web the window attributes need a canvas, e.g. `WindowAttributesExtWebSys::with_append(true)`
+pub fn run(
+    render_builder: impl FnOnce(&mut Render) + 'static,
+    window_attributes: WindowAttributes,
+) {
+    let event_loop = EventLoop::<GfxEvent>::with_user_event().build().unwrap();
+    event_loop.set_control_flow(ControlFlow::Poll);
+    let runner = Runner {
+        render_builder: Some(Box::new(render_builder)),
+        window_attributes,
+        window: None,
+        proxy: event_loop.create_proxy(),
+        render: Render::new(),
+        readied: false,
+        recovering: false,
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut runner = runner;
+        if let Err(e) = event_loop.run_app(&mut runner) {
+            tracing::error!("ready_paint::run: the event loop failed: {}", e);
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::EventLoopExtWebSys;
+        event_loop.spawn_app(runner);
+    }
+}
+
+type RenderBuilder = Box<dyn FnOnce(&mut Render)>;
+
+/// gfx built or recovered asynchronously on the web
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+enum GfxEvent {
+    Ready(Gfx),
+    Recovered(Result<Gfx, GfxError>),
+}
+
+struct Runner {
+    render_builder: Option<RenderBuilder>,
+    window_attributes: WindowAttributes,
+    window: Option<Arc<Window>>,
+    /// hand the gfx built or recovered asynchronously on the web back to the event loop
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    proxy: EventLoopProxy<GfxEvent>,
+    render: Render,
+    readied: bool,
+    /// the lost gfx is being recovered on the web, nothing to paint until it's back
+    recovering: bool,
+}
+
+impl Runner {
+    fn install(&mut self, gfx: Gfx) {
+        self.render.entry = RenderEntry::Ready(gfx);
+        if let Some(render_builder) = self.render_builder.take() {
+            render_builder(&mut self.render);
+        }
+        let size = self.window.as_ref().unwrap().inner_size();
+        self.configure(size.width, size.height);
+    }
+
+    /// ready the scenes the first time the surface has a size
+    fn configure(&mut self, width: u32, height: u32) {
+        let Some(gfx) = self.render.gfx_mut() else {
+            return;
+        };
+        if width == 0 || height == 0 {
+            return;
+        }
+        gfx.resize(width, height);
+        if !self.readied {
+            self.readied = true;
+            self.render.ready();
+        }
+        self.window.as_ref().unwrap().request_redraw();
+    }
+
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn redraw(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.readied || self.recovering {
+            return;
+        }
+        if self.render.is_device_lost() {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Err(e) = pollster::block_on(self.render.recover(self.window.clone().unwrap())) {
+                tracing::error!("ready_paint::run can't recover the lost device: {}", e);
+                return event_loop.exit();
+            }
+            #[cfg(target_arch = "wasm32")]
+            return self.recover();
+        }
+        if self.render.gfx_mut().is_some_and(Gfx::pace) {
+            self.render.paint();
+        }
+    }
+
+    /// `Render::recover` without blocking: the lost gfx is recovered in a future
+    /// and comes back as `GfxEvent::Recovered`
+    #[cfg(target_arch = "wasm32")]
+    fn recover(&mut self) {
+        let RenderEntry::Ready(gfx) = std::mem::take(&mut self.render.entry) else {
+            return;
+        };
+        tracing::warn!("ready_paint::run: the device is lost, recovering");
+        self.recovering = true;
+        let window = self.window.clone().unwrap();
+        let proxy = self.proxy.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let recovered = gfx.recover(window).await;
+            if proxy.send_event(GfxEvent::Recovered(recovered)).is_err() {
+                tracing::error!("ready_paint::run: the event loop closed before gfx was recovered");
+            }
+        });
+    }
+}
+
+impl ApplicationHandler<GfxEvent> for Runner {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let window = Arc::new(
+            event_loop
+                .create_window(self.window_attributes.clone())
+                .unwrap(),
+        );
+        self.window = Some(window.clone());
+        #[cfg(not(target_arch = "wasm32"))]
+        self.install(pollster::block_on(Gfx::new(window)));
+        #[cfg(target_arch = "wasm32")]
+        {
+            let proxy = self.proxy.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if proxy
+                    .send_event(GfxEvent::Ready(Gfx::new(window).await))
+                    .is_err()
+                {
+                    tracing::error!("ready_paint::run: the event loop closed before gfx was ready");
+                }
+            });
+        }
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: GfxEvent) {
+        match event {
+            GfxEvent::Ready(gfx) => self.install(gfx),
+            GfxEvent::Recovered(Ok(gfx)) => {
+                self.recovering = false;
+                self.render.recovered(gfx);
+                // the window may have been resized while recovering
+                let size = self.window.as_ref().unwrap().inner_size();
+                self.configure(size.width, size.height);
+            }
+            GfxEvent::Recovered(Err(e)) => {
+                tracing::error!("ready_paint::run can't recover the lost device: {}", e);
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        self.render.handle_event(&event);
+        match event {
+            WindowEvent::Resized(size) => self.configure(size.width, size.height),
+            // the scenes see it first (e.g. to save), consuming it doesn't keep the window open
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::RedrawRequested => self.redraw(event_loop),
+            _ => (),
+        }
+    }
+
+    fn about_to_wait(&mut self, _: &ActiveEventLoop) {
+        if self.readied {
+            self.window.as_ref().unwrap().request_redraw();
+        }
+    }
+}
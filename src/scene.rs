@@ -1,7 +1,17 @@
-use crate::gfx::Gfx;
+use crate::{
+    events::Events,
+    gfx::{Frame, Gfx},
+    state::State,
+    time::FixedTime,
+    timing::{SystemTimings, TimingReport},
+};
 use std::{
     any::{Any, TypeId},
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
 };
 pub type HashTypeId2Data = HashMap<TypeId, Box<dyn Any>>;
 /// Render -> Scene
@@ -18,6 +28,18 @@ pub trait Ready {
 pub trait Paint {
     fn paint(data: &mut HashTypeId2Data, gfx: &Gfx);
 }
+
+pub type SystemResult = Result<(), Box<dyn std::error::Error>>;
+
+/// Ready that can fail, register with `Scene::add_try_ready`, the error is handled by the `ErrorPolicy`
+pub trait TryReady {
+    fn try_ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) -> SystemResult;
+}
+
+/// Paint that can fail, register with `Scene::add_try_paint`, the error is handled by the `ErrorPolicy`
+pub trait TryPaint {
+    fn try_paint(data: &mut HashTypeId2Data, gfx: &Gfx) -> SystemResult;
+}
 /// running in Paint function {
 ///    [update, update, ..]
 ///       (may also have)
@@ -35,6 +57,49 @@ pub trait Pass<'a> {
     fn pass(data: &mut HashTypeId2Data, render_pass: wgpu::RenderPass<'a>) -> wgpu::RenderPass<'a>;
 }
 
+/// recorded before the paints of the frame, the computes of a scene go in one queue submit,
+/// set pipeline and bind groups here, the library dispatch the workgroups given to `Scene::add_compute`
+pub trait Compute<'a> {
+    fn compute(
+        data: &mut HashTypeId2Data,
+        compute_pass: wgpu::ComputePass<'a>,
+    ) -> wgpu::ComputePass<'a>;
+}
+
+/// workgroups needed to cover `items` with `workgroup_size` threads each
+pub fn workgroup_count(items: u32, workgroup_size: u32) -> u32 {
+    items.div_ceil(workgroup_size.max(1))
+}
+
+/// drawn by `draw_overlays` on top of what a Paint drew into its frame (debug draw, text ..)
+/// prepare run before the render pass begin, to upload what was queued in this frame
+pub trait Overlay {
+    fn prepare(data: &mut HashTypeId2Data, gfx: &Gfx, encoder: &mut wgpu::CommandEncoder);
+    fn draw<'a>(
+        data: &mut HashTypeId2Data,
+        render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a>;
+    /// run after every `Scene::paint` whether the overlay was drawn or not (no `draw_overlays`,
+    /// surface error, disabled scene ..), drop what was queued so it doesn't pile up
+    fn clear(_data: &mut HashTypeId2Data) {}
+}
+
+/// keep cpu side state across `Render::recover`,
+/// save run before the readys run again on the new device, restore after them
+pub trait Recover {
+    type State: 'static;
+    fn save(data: &HashTypeId2Data) -> Self::State;
+    fn restore(data: &mut HashTypeId2Data, state: Self::State);
+}
+
+/// raw window events (file drops, focus, ime ..) handed by `Render::handle_event` to each scene in order,
+/// return true to consume the event, the handlers and scenes after this one don't see it then.
+/// `ready_paint::run` closes the window on `CloseRequested` whether it was consumed or not
+#[cfg(feature = "winit")]
+pub trait OnEvent {
+    fn on_event(data: &mut HashTypeId2Data, gfx: &Gfx, event: &winit::event::WindowEvent) -> bool;
+}
+
 /// Render -> Scene
 ///        -> Scene is impl Queue (mean its process in sequence)
 ///        -> Queue just inroduce your Scene
@@ -46,19 +111,23 @@ pub trait Queue {
 pub fn get_res<T: Any + 'static>(data: &HashTypeId2Data) -> &T {
     data.get(&TypeId::of::<T>())
         .and_then(|data| data.downcast_ref::<T>())
-        .expect(&format!(
-            "Failed to get resource of type: {}",
-            std::any::type_name::<T>()
-        ))
+        .unwrap_or_else(|| {
+            panic!(
+                "Failed to get resource of type: {}",
+                std::any::type_name::<T>()
+            )
+        })
 }
 /// get ref mut from hashmap by type into box data
 pub fn get_res_mut<T: Any + 'static>(data: &mut HashTypeId2Data) -> &mut T {
     data.get_mut(&TypeId::of::<T>())
         .and_then(|data| data.downcast_mut::<T>())
-        .expect(&format!(
-            "Failed to get mutable resource of type: {}",
-            std::any::type_name::<T>()
-        ))
+        .unwrap_or_else(|| {
+            panic!(
+                "Failed to get mutable resource of type: {}",
+                std::any::type_name::<T>()
+            )
+        })
 }
 
 /// get ref and mut from hashmap by type into box data
@@ -75,17 +144,21 @@ pub fn get_ref_and_mut<Ref: Any + 'static, Mut: Any + 'static>(
         let t1 = (&*data_ptr)
             .get(&TypeId::of::<Ref>())
             .and_then(|r| r.downcast_ref::<Ref>())
-            .expect(&format!(
-                "Failed to get resource of type: {}",
-                std::any::type_name::<Ref>()
-            ));
+            .unwrap_or_else(|| {
+                panic!(
+                    "Failed to get resource of type: {}",
+                    std::any::type_name::<Ref>()
+                )
+            });
         let t2 = (&mut *data_ptr)
             .get_mut(&TypeId::of::<Mut>())
             .and_then(|d| d.downcast_mut::<Mut>())
-            .expect(&format!(
-                "Failed to get mutable resource of type: {}",
-                std::any::type_name::<Mut>()
-            ));
+            .unwrap_or_else(|| {
+                panic!(
+                    "Failed to get mutable resource of type: {}",
+                    std::any::type_name::<Mut>()
+                )
+            });
         (t1, t2)
     }
 }
@@ -95,24 +168,202 @@ pub fn return_res<T: Any + 'static>(data: &mut HashMap<TypeId, Box<dyn Any>>, ne
     data.insert(TypeId::of::<T>(), Box::new(new_data));
 }
 
+/// draw the overlays of the scene (see `Scene::add_overlay`) into frame, each in its own render pass
+/// loading what is already there, in error scopes of their own.
+/// call it from the Paint presenting the frame, after its own passes:
+/// ```ignore
+/// let Ok(mut frame) = gfx.begin_frame() else { return };
+/// frame.clear_color = Some(wgpu::Color::BLACK);
+/// MyPass::pass(data, frame.begin_render_pass("my pass"));
+/// frame.end_pass();
+/// draw_overlays(data, gfx, &mut frame);
+/// gfx.end_frame(frame);
+/// ```
+pub fn draw_overlays(data: &mut HashTypeId2Data, gfx: &Gfx, frame: &mut Frame) {
+    let Some(Overlays {
+        scene, overlays, ..
+    }) = data
+        .get(&TypeId::of::<Overlays>())
+        .and_then(|res| res.downcast_ref::<Overlays>())
+    else {
+        return;
+    };
+    let overlays = overlays.clone();
+    frame.enter_scene(scene);
+    let mut scopes = Vec::new();
+    for overlay in overlays.iter() {
+        let _span = tracing::trace_span!("system", system = overlay.name).entered();
+        push_error_scopes(gfx);
+        (overlay.prepare)(data, gfx, &mut frame.encoder);
+        pop_error_scopes(gfx, overlay.type_id, &mut scopes);
+    }
+    for overlay in overlays.iter() {
+        let _span = tracing::trace_span!("system", system = overlay.name).entered();
+        push_error_scopes(gfx);
+        let render_pass = frame.begin_load_pass(overlay.name);
+        (overlay.draw)(data, render_pass);
+        frame.end_pass();
+        pop_error_scopes(gfx, overlay.type_id, &mut scopes);
+    }
+    get_res_mut::<Overlays>(data)
+        .error_scopes
+        .append(&mut scopes);
+}
+
+/// errors kept by each scene, the older ones are dropped
+const MAX_ERRORS: usize = 16;
+
+/// what a scene does when one of its systems fails, set for every scene with `Render::set_error_policy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// panic with the scene and system name
+    #[default]
+    Panic,
+    /// print and keep the error, that system doesn't run anymore
+    SkipSystem,
+    /// print and keep the error, the whole scene is disabled (see `Render::set_enabled`)
+    DisableScene,
+}
+
+/// a `TryReady`/`TryPaint` error, or a wgpu validation or out of memory error raised inside one system
+#[derive(Debug)]
+pub struct SystemError {
+    pub scene: String,
+    pub system: &'static str,
+    pub error: Box<dyn std::error::Error>,
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error in scene `{}` system `{}`: {}",
+            self.scene, self.system, self.error
+        )
+    }
+}
+
+impl std::error::Error for SystemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+type ErrorScope = Pin<Box<dyn Future<Output = Option<wgpu::Error>>>>;
+type ReadyFn = Box<dyn FnMut(&mut HashTypeId2Data, &Gfx) -> SystemResult>;
+type PaintFn = Box<dyn Fn(&mut HashTypeId2Data, &Gfx) -> SystemResult>;
+#[cfg(feature = "winit")]
+type EventFn = Box<dyn Fn(&mut HashTypeId2Data, &Gfx, &winit::event::WindowEvent) -> bool>;
+type RestoreFn = Box<dyn FnOnce(&mut HashTypeId2Data)>;
+type SaveFn = Box<dyn Fn(&HashTypeId2Data) -> RestoreFn>;
+type ComputeFn =
+    Box<dyn for<'a> Fn(&mut HashTypeId2Data, wgpu::ComputePass<'a>) -> wgpu::ComputePass<'a>>;
+type DispatchFn = Box<dyn Fn(&HashTypeId2Data) -> [u32; 3]>;
+type ConditionFn = Box<dyn Fn(&HashTypeId2Data) -> bool>;
+
+struct ComputeFns {
+    type_id: TypeId,
+    name: &'static str,
+    compute: ComputeFn,
+    dispatch: DispatchFn,
+}
+
+/// the `State<S>` methods of one registered state type
+struct StateFns {
+    begin: fn(&mut HashTypeId2Data) -> bool,
+    switch: fn(&mut HashTypeId2Data),
+    end: fn(&mut HashTypeId2Data),
+}
+
+/// a Paint run when `when` holds during a transition
+struct StateHook {
+    type_id: TypeId,
+    name: &'static str,
+    when: ConditionFn,
+    paint: PaintFn,
+}
+
+#[derive(Clone, Copy)]
+struct OverlayFns {
+    type_id: TypeId,
+    name: &'static str,
+    prepare: fn(&mut HashTypeId2Data, &Gfx, &mut wgpu::CommandEncoder),
+    draw: for<'a> fn(&mut HashTypeId2Data, wgpu::RenderPass<'a>) -> wgpu::RenderPass<'a>,
+    clear: fn(&mut HashTypeId2Data),
+}
+
+/// resource of the overlays registered with `Scene::add_overlay`, so `draw_overlays` reach them from a Paint
+struct Overlays {
+    scene: String,
+    overlays: Vec<OverlayFns>,
+    /// popped by `draw_overlays`, handled with the errors of the scene
+    error_scopes: Vec<(TypeId, ErrorScope)>,
+}
+
 pub struct Scene {
     name: String,
+    enabled: bool,
     res: HashMap<TypeId, Box<dyn Any>>,
     readys: Vec<TypeId>,
     paints: Vec<TypeId>,
-    readys_hashmap: HashMap<TypeId, Box<dyn FnMut(&mut HashMap<TypeId, Box<dyn Any>>, &Gfx)>>,
-    paints_hashmap: HashMap<TypeId, Box<dyn Fn(&mut HashMap<TypeId, Box<dyn Any>>, &Gfx)>>,
+    readys_hashmap: HashMap<TypeId, ReadyFn>,
+    paints_hashmap: HashMap<TypeId, PaintFn>,
+    /// paints skipped unless their condition holds, see `add_paint_in`
+    paint_conditions: HashMap<TypeId, ConditionFn>,
+    /// run `FixedTime::steps` times before the paints
+    fixed_updates: Vec<(TypeId, PaintFn)>,
+    states: Vec<StateFns>,
+    on_exits: Vec<StateHook>,
+    on_enters: Vec<StateHook>,
+    computes: Vec<ComputeFns>,
+    recovers: Vec<SaveFn>,
+    event_updates: Vec<fn(&mut HashTypeId2Data)>,
+    #[cfg(feature = "winit")]
+    event_handlers: Vec<EventFn>,
+    system_names: HashMap<TypeId, &'static str>,
+    /// popped error scopes not resolved yet (on the web they resolve later)
+    error_scopes: Vec<(TypeId, ErrorScope)>,
+    /// errors returned by systems this frame, handled with the error scopes
+    failed: Vec<(TypeId, Box<dyn std::error::Error>)>,
+    errors: Vec<SystemError>,
+    error_policy: ErrorPolicy,
+    timings: SystemTimings,
+    disabled: HashSet<TypeId>,
+    /// error scopes of the readys, their errors add to `failed_readys` whenever they resolve
+    ready_error_scopes: Vec<(TypeId, ErrorScope)>,
+    /// readys that failed under `ErrorPolicy::DisableScene`, run again by `Scene::retry_readys`
+    failed_readys: Vec<TypeId>,
 }
 
 impl Scene {
     pub fn new(name: String) -> Self {
         Scene {
             name,
+            enabled: true,
             res: HashMap::new(),
             readys: Vec::new(),
             paints: Vec::new(),
             readys_hashmap: HashMap::new(),
             paints_hashmap: HashMap::new(),
+            paint_conditions: HashMap::new(),
+            fixed_updates: Vec::new(),
+            states: Vec::new(),
+            on_exits: Vec::new(),
+            on_enters: Vec::new(),
+            computes: Vec::new(),
+            recovers: Vec::new(),
+            event_updates: Vec::new(),
+            #[cfg(feature = "winit")]
+            event_handlers: Vec::new(),
+            system_names: HashMap::new(),
+            error_scopes: Vec::new(),
+            failed: Vec::new(),
+            errors: Vec::new(),
+            error_policy: ErrorPolicy::default(),
+            timings: SystemTimings::default(),
+            disabled: HashSet::new(),
+            ready_error_scopes: Vec::new(),
+            failed_readys: Vec::new(),
         }
     }
 
@@ -120,40 +371,869 @@ impl Scene {
         &self.name
     }
 
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// see `Render::set_enabled`, a scene with failed readys stays disabled
+    /// until `Scene::retry_readys` succeeds, its paints would miss their resources
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.failed_readys.is_empty() {
+            tracing::warn!(scene = %self.name, "scene has failed readys, not enabled");
+            return;
+        }
+        self.enabled = enabled;
+    }
+
+    /// readys that failed and disabled the scene
+    pub fn failed_readys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.failed_readys
+            .iter()
+            .map(|type_id| self.system_names[type_id])
+    }
+
     pub fn add_ready<T: Ready + Default + 'static>(&mut self, mut ready_res: T) -> &mut Self {
         let type_id = TypeId::of::<T>();
         self.readys.push(type_id);
+        self.system_names
+            .insert(type_id, std::any::type_name::<T>());
         self.res.insert(type_id, Box::new(T::default()));
         self.readys_hashmap.insert(
             type_id,
             Box::new(move |data, gfx| {
                 ready_res.ready(data, gfx);
+                Ok(())
             }),
         );
 
         self
     }
 
+    /// like `add_ready`, an error is handled by the `ErrorPolicy` of the scene
+    pub fn add_try_ready<T: TryReady + Default + 'static>(
+        &mut self,
+        mut ready_res: T,
+    ) -> &mut Self {
+        let type_id = TypeId::of::<T>();
+        self.readys.push(type_id);
+        self.system_names
+            .insert(type_id, std::any::type_name::<T>());
+        self.res.insert(type_id, Box::new(T::default()));
+        self.readys_hashmap.insert(
+            type_id,
+            Box::new(move |data, gfx| ready_res.try_ready(data, gfx)),
+        );
+        self
+    }
+
     pub fn add_paint<T: Paint + 'static>(&mut self) {
+        self.insert_paint::<T>(Box::new(|data, gfx| {
+            T::paint(data, gfx);
+            Ok(())
+        }));
+    }
+
+    /// like `add_paint`, an error is handled by the `ErrorPolicy` of the scene
+    pub fn add_try_paint<T: TryPaint + 'static>(&mut self) -> &mut Self {
+        self.insert_paint::<T>(Box::new(T::try_paint));
+        self
+    }
+
+    fn insert_paint<T: 'static>(&mut self, paint: PaintFn) {
         let type_id = TypeId::of::<T>();
         self.paints.push(type_id);
-        self.paints_hashmap.insert(type_id, Box::new(T::paint));
+        self.system_names
+            .insert(type_id, std::any::type_name::<T>());
+        self.paints_hashmap.insert(type_id, paint);
+    }
+
+    /// update run on the fixed timestep of the scene, read the step with `get_res::<FixedTime>(data).step()`.
+    /// inserts a default `FixedTime` (60 Hz) unless `set_fixed_time` was called
+    pub fn add_fixed_update<T: Update + 'static>(&mut self) -> &mut Self {
+        let type_id = TypeId::of::<T>();
+        self.system_names
+            .insert(type_id, std::any::type_name::<T>());
+        self.fixed_updates.push((
+            type_id,
+            Box::new(|data, gfx| {
+                T::update(data, gfx);
+                Ok(())
+            }),
+        ));
+        self.res
+            .entry(TypeId::of::<FixedTime>())
+            .or_insert_with(|| Box::new(FixedTime::default()));
+        self
+    }
+
+    /// e.g. `FixedTime::new(1. / 120.).with_max_substeps(4)`
+    pub fn set_fixed_time(&mut self, fixed_time: FixedTime) -> &mut Self {
+        return_res(&mut self.res, fixed_time);
+        self
+    }
+
+    /// insert `State<S>` in the resources, its transitions are applied at the start of `Scene::paint`
+    pub fn add_state<S: Copy + PartialEq + 'static>(&mut self, initial: S) -> &mut Self {
+        if let Entry::Vacant(entry) = self.res.entry(TypeId::of::<State<S>>()) {
+            entry.insert(Box::new(State::new(initial)));
+            self.states.push(StateFns {
+                begin: |data| get_res_mut::<State<S>>(data).begin_transition(),
+                switch: |data| get_res_mut::<State<S>>(data).switch(),
+                end: |data| get_res_mut::<State<S>>(data).end_transition(),
+            });
+        }
+        self
+    }
+
+    /// a paint that only runs while `State<S>` is `state`
+    pub fn add_paint_in<T: Paint + 'static, S: Copy + PartialEq + 'static>(
+        &mut self,
+        state: S,
+    ) -> &mut Self {
+        self.add_paint::<T>();
+        self.paint_conditions.insert(
+            TypeId::of::<T>(),
+            Box::new(move |data| get_res::<State<S>>(data).is(state)),
+        );
+        self
+    }
+
+    /// run the paint of `T` once when `State<S>` becomes `state`, after the exit hooks
+    pub fn add_on_enter<T: Paint + 'static, S: Copy + PartialEq + 'static>(
+        &mut self,
+        state: S,
+    ) -> &mut Self {
+        let hook = self.state_hook::<T>(move |data| get_res::<State<S>>(data).entering(state));
+        self.on_enters.push(hook);
+        self
+    }
+
+    /// run the paint of `T` once when `State<S>` leaves `state`, it is still the current state
+    pub fn add_on_exit<T: Paint + 'static, S: Copy + PartialEq + 'static>(
+        &mut self,
+        state: S,
+    ) -> &mut Self {
+        let hook = self.state_hook::<T>(move |data| get_res::<State<S>>(data).exiting(state));
+        self.on_exits.push(hook);
+        self
+    }
+
+    fn state_hook<T: Paint + 'static>(
+        &mut self,
+        when: impl Fn(&HashTypeId2Data) -> bool + 'static,
+    ) -> StateHook {
+        let type_id = TypeId::of::<T>();
+        let name = std::any::type_name::<T>();
+        self.system_names.insert(type_id, name);
+        StateHook {
+            type_id,
+            name,
+            when: Box::new(when),
+            paint: Box::new(|data, gfx| {
+                T::paint(data, gfx);
+                Ok(())
+            }),
+        }
+    }
+
+    /// compute work submitted by `Render::paint` before the paints of every scene,
+    /// `dispatch` give the workgroup counts from the resources each frame, zero skips the dispatch
+    /// ```ignore
+    /// scene.add_compute::<Particles>(|data| {
+    ///     [workgroup_count(get_res::<Particles>(data).count, 64), 1, 1]
+    /// });
+    /// ```
+    pub fn add_compute<T: for<'a> Compute<'a> + 'static>(
+        &mut self,
+        dispatch: impl Fn(&HashTypeId2Data) -> [u32; 3] + 'static,
+    ) -> &mut Self {
+        let type_id = TypeId::of::<T>();
+        let name = std::any::type_name::<T>();
+        self.system_names.insert(type_id, name);
+        self.computes.push(ComputeFns {
+            type_id,
+            name,
+            compute: Box::new(
+                |data: &mut HashTypeId2Data, compute_pass: wgpu::ComputePass<'_>| {
+                    T::compute(data, compute_pass)
+                },
+            ),
+            dispatch: Box::new(dispatch),
+        });
+        self
+    }
+
+    /// add as Ready, the Paint presenting the frame draws it with `draw_overlays`
+    pub fn add_overlay<T: Overlay + Ready + Default + 'static>(&mut self, overlay: T) -> &mut Self {
+        self.add_ready(overlay);
+        let name = self.name.clone();
+        self.res
+            .entry(TypeId::of::<Overlays>())
+            .or_insert_with(|| {
+                Box::new(Overlays {
+                    scene: name,
+                    overlays: Vec::new(),
+                    error_scopes: Vec::new(),
+                })
+            })
+            .downcast_mut::<Overlays>()
+            .unwrap()
+            .overlays
+            .push(OverlayFns {
+                type_id: TypeId::of::<T>(),
+                name: std::any::type_name::<T>(),
+                prepare: T::prepare,
+                draw: T::draw,
+                clear: T::clear,
+            });
+        self
+    }
+
+    /// handlers run in registration order
+    #[cfg(feature = "winit")]
+    pub fn add_on_event<T: OnEvent + 'static>(&mut self) -> &mut Self {
+        self.event_handlers.push(Box::new(T::on_event));
+        self
+    }
+
+    /// true when one of the handlers consumed the event
+    #[cfg(feature = "winit")]
+    pub fn handle_event(&mut self, gfx: &Gfx, event: &winit::event::WindowEvent) -> bool {
+        self.event_handlers
+            .iter()
+            .any(|handler| handler(&mut self.res, gfx, event))
+    }
+
+    /// shorthand for `ErrorPolicy::SkipSystem`, or back to `ErrorPolicy::Panic`
+    pub fn disable_on_error(&mut self, disable: bool) -> &mut Self {
+        self.error_policy = if disable {
+            ErrorPolicy::SkipSystem
+        } else {
+            ErrorPolicy::Panic
+        };
+        self
+    }
+
+    /// overridden by a later `Render::set_error_policy`
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) -> &mut Self {
+        self.error_policy = policy;
+        self
+    }
+
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    /// the last errors not taken yet, oldest first, unless the policy panics
+    pub fn errors(&self) -> &[SystemError] {
+        &self.errors
+    }
+
+    pub fn last_error(&self) -> Option<&SystemError> {
+        self.errors.last()
+    }
+
+    pub fn take_errors(&mut self) -> Vec<SystemError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// measure the cpu time of every ready, fixed update and paint, see `Render::set_system_timing`
+    pub fn set_system_timing(&mut self, enabled: bool) -> &mut Self {
+        self.timings.set_enabled(enabled);
+        self
+    }
+
+    pub fn system_timing(&self) -> bool {
+        self.timings.is_enabled()
+    }
+
+    /// systems measured so far in the order they run, `to_string` gives a text table
+    pub fn timing_report(&self) -> TimingReport {
+        let mut hooks: Vec<TypeId> = Vec::new();
+        for hook in self.on_exits.iter().chain(self.on_enters.iter()) {
+            if !hooks.contains(&hook.type_id) {
+                hooks.push(hook.type_id);
+            }
+        }
+        let phases = [
+            ("ready", &self.readys),
+            (
+                "compute",
+                &self.computes.iter().map(|c| c.type_id).collect(),
+            ),
+            ("hook", &hooks),
+            (
+                "fixed_update",
+                &self.fixed_updates.iter().map(|(id, _)| *id).collect(),
+            ),
+            ("paint", &self.paints),
+        ];
+        let entries = phases
+            .into_iter()
+            .flat_map(|(phase, type_ids)| {
+                type_ids.iter().filter_map(move |type_id| {
+                    self.timings
+                        .entry(&self.name, self.system_names[type_id], *type_id, phase)
+                })
+            })
+            .collect();
+        TimingReport { entries }
+    }
+
+    pub fn reset_timings(&mut self) {
+        self.timings.reset();
+    }
+
+    /// systems disabled after an error
+    pub fn disabled_systems(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.disabled
+            .iter()
+            .map(|type_id| self.system_names[type_id])
+    }
+
+    /// move in the globals this scene has no resource of the same type for
+    pub(crate) fn lend_globals(&mut self, globals: &mut HashTypeId2Data) -> Vec<TypeId> {
+        let lent: Vec<TypeId> = globals
+            .keys()
+            .filter(|type_id| !self.res.contains_key(type_id))
+            .copied()
+            .collect();
+        for type_id in lent.iter() {
+            self.res.insert(*type_id, globals.remove(type_id).unwrap());
+        }
+        lent
+    }
+
+    /// move the lent globals back, a system may have replaced them with `return_res`
+    pub(crate) fn return_globals(&mut self, globals: &mut HashTypeId2Data, lent: Vec<TypeId>) {
+        for type_id in lent {
+            if let Some(res) = self.res.remove(&type_id) {
+                globals.insert(type_id, res);
+            }
+        }
+    }
+
+    /// insert `Events<T>` in the resources, its buffers are swapped at the end of every `Scene::paint`
+    pub fn add_event<T: 'static>(&mut self) -> &mut Self {
+        if let Entry::Vacant(entry) = self.res.entry(TypeId::of::<Events<T>>()) {
+            entry.insert(Box::new(Events::<T>::default()));
+            self.event_updates
+                .push(|data| get_res_mut::<Events<T>>(data).update());
+        }
+        self
+    }
+
+    /// e.g. keep a camera position or simulation state that its Ready would reset
+    pub fn add_recover<T: Recover + 'static>(&mut self) -> &mut Self {
+        self.recovers
+            .push(Box::new(|data: &HashTypeId2Data| -> RestoreFn {
+                let state = T::save(data);
+                Box::new(move |data: &mut HashTypeId2Data| T::restore(data, state))
+            }));
+        self
     }
 
     pub fn ready(&mut self, gfx: &Gfx) {
-        println!("<Scene>::ready");
+        let _span = tracing::debug_span!("ready", scene = %self.name).entered();
+        self.failed_readys.clear();
+        self.run_readys(gfx, &[]);
+    }
+
+    /// run the readys that failed again in order, true when they all succeeded.
+    /// `Render::set_enabled` call it before enabling the scene
+    pub fn retry_readys(&mut self, gfx: &Gfx) -> bool {
+        let _span = tracing::debug_span!("retry_readys", scene = %self.name).entered();
+        let failed = std::mem::take(&mut self.failed_readys);
+        if !failed.is_empty() {
+            self.run_readys(gfx, &failed);
+        }
+        self.failed_readys.is_empty()
+    }
+
+    /// run the readys in `only`, or all of them when empty
+    fn run_readys(&mut self, gfx: &Gfx, only: &[TypeId]) {
+        let mut errors = Vec::new();
         for ready_type_id in self.readys.iter() {
+            if self.disabled.contains(ready_type_id)
+                || !(only.is_empty() || only.contains(ready_type_id))
+            {
+                continue;
+            }
             if let Some(ready_fn) = self.readys_hashmap.get_mut(ready_type_id) {
-                ready_fn(&mut self.res, gfx);
+                let _span =
+                    tracing::trace_span!("system", system = self.system_names[ready_type_id])
+                        .entered();
+                push_error_scopes(gfx);
+                let start = self.timings.start();
+                let result = ready_fn(&mut self.res, gfx);
+                self.timings.record(*ready_type_id, "ready", start);
+                pop_error_scopes(gfx, *ready_type_id, &mut self.ready_error_scopes);
+                if let Err(error) = result {
+                    errors.push((*ready_type_id, error));
+                }
             }
         }
+        for (type_id, error) in errors {
+            self.report(type_id, error, true);
+        }
+        self.handle_errors();
+    }
+
+    /// run every ready again on a recovered gfx, around the registered Recover hooks
+    pub fn recover(&mut self, gfx: &Gfx) {
+        let _span = tracing::debug_span!("recover", scene = %self.name).entered();
+        let restores: Vec<RestoreFn> = self.recovers.iter().map(|save| save(&self.res)).collect();
+        self.ready(gfx);
+        for restore in restores {
+            restore(&mut self.res);
+        }
     }
 
     pub fn paint(&mut self, gfx: &Gfx) {
-        for paint_type_id in self.paints.iter() {
-            if let Some(paint_fn) = self.paints_hashmap.get_mut(paint_type_id) {
-                paint_fn(&mut self.res, gfx);
+        let _span = tracing::debug_span!("paint", scene = %self.name).entered();
+        gfx.enter_scene(&self.name);
+        self.apply_states(gfx);
+        if !self.disabled_by_error() {
+            self.fixed_update(gfx);
+            self.run_paints(gfx);
+        }
+        self.handle_errors();
+        self.clear_overlays();
+        for update in self.event_updates.iter() {
+            update(&mut self.res);
+        }
+    }
+
+    /// `Overlay::clear` of every overlay, end of `Scene::paint` or while the scene is disabled
+    pub(crate) fn clear_overlays(&mut self) {
+        let Some(overlays) = self
+            .res
+            .get(&TypeId::of::<Overlays>())
+            .and_then(|res| res.downcast_ref::<Overlays>())
+        else {
+            return;
+        };
+        for clear in overlays
+            .overlays
+            .iter()
+            .map(|o| o.clear)
+            .collect::<Vec<_>>()
+        {
+            clear(&mut self.res);
+        }
+    }
+
+    /// stop at the system disabling the scene, see `disabled_by_error`
+    fn run_paints(&mut self, gfx: &Gfx) {
+        for index in 0..self.paints.len() {
+            let paint_type_id = self.paints[index];
+            if self.disabled.contains(&paint_type_id) {
+                continue;
+            }
+            if let Some(condition) = self.paint_conditions.get(&paint_type_id) {
+                if !condition(&self.res) {
+                    continue;
+                }
+            }
+            if let Some(paint_fn) = self.paints_hashmap.get_mut(&paint_type_id) {
+                let _span =
+                    tracing::trace_span!("system", system = self.system_names[&paint_type_id])
+                        .entered();
+                push_error_scopes(gfx);
+                let start = self.timings.start();
+                let result = paint_fn(&mut self.res, gfx);
+                self.timings.record(paint_type_id, "paint", start);
+                if let Err(error) = result {
+                    self.failed.push((paint_type_id, error));
+                }
+                pop_error_scopes(gfx, paint_type_id, &mut self.error_scopes);
+            }
+            if self.disabled_by_error() {
+                return;
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, gfx: &Gfx) {
+        if self.fixed_updates.is_empty() {
+            return;
+        }
+        let steps = get_res_mut::<FixedTime>(&mut self.res).accumulate(gfx.time.delta());
+        for _ in 0..steps {
+            for index in 0..self.fixed_updates.len() {
+                let (type_id, update) = &self.fixed_updates[index];
+                let type_id = *type_id;
+                if self.disabled.contains(&type_id) {
+                    continue;
+                }
+                let _span =
+                    tracing::trace_span!("system", system = self.system_names[&type_id]).entered();
+                push_error_scopes(gfx);
+                let start = self.timings.start();
+                let result = update(&mut self.res, gfx);
+                self.timings.record(type_id, "fixed_update", start);
+                if let Err(error) = result {
+                    self.failed.push((type_id, error));
+                }
+                pop_error_scopes(gfx, type_id, &mut self.error_scopes);
+                if self.disabled_by_error() {
+                    return;
+                }
             }
         }
     }
+
+    /// under `ErrorPolicy::DisableScene` handle the errors right after each system, so the
+    /// systems after a failing one don't run in the frame it disabled the scene.
+    /// on the web the wgpu errors only resolve later
+    fn disabled_by_error(&mut self) -> bool {
+        if self.error_policy != ErrorPolicy::DisableScene {
+            return false;
+        }
+        self.handle_errors();
+        !self.enabled
+    }
+
+    /// transitions requested during the last frame: exit hooks, switch, enter hooks
+    fn apply_states(&mut self, gfx: &Gfx) {
+        for state in self.states.iter() {
+            if !(state.begin)(&mut self.res) {
+                continue;
+            }
+            run_hooks(
+                &self.on_exits,
+                &mut self.res,
+                gfx,
+                &self.disabled,
+                &mut self.error_scopes,
+                &mut self.failed,
+                &mut self.timings,
+            );
+            (state.switch)(&mut self.res);
+            run_hooks(
+                &self.on_enters,
+                &mut self.res,
+                gfx,
+                &self.disabled,
+                &mut self.error_scopes,
+                &mut self.failed,
+                &mut self.timings,
+            );
+            (state.end)(&mut self.res);
+        }
+    }
+
+    /// returned errors, then the wgpu errors of the resolved error scopes
+    fn handle_errors(&mut self) {
+        if let Some(overlays) = self
+            .res
+            .get_mut(&TypeId::of::<Overlays>())
+            .and_then(|res| res.downcast_mut::<Overlays>())
+        {
+            self.error_scopes.append(&mut overlays.error_scopes);
+        }
+        for (type_id, error) in std::mem::take(&mut self.failed) {
+            self.report(type_id, error, false);
+        }
+        for (type_id, error) in resolved_errors(&mut self.ready_error_scopes) {
+            self.report(type_id, Box::new(error), true);
+        }
+        for (type_id, error) in resolved_errors(&mut self.error_scopes) {
+            self.report(type_id, Box::new(error), false);
+        }
+    }
+
+    /// ready is true for the errors of a Ready, kept in `failed_readys` when it disables the scene
+    fn report(&mut self, type_id: TypeId, error: Box<dyn std::error::Error>, ready: bool) {
+        let error = SystemError {
+            scene: self.name.clone(),
+            system: self.system_names[&type_id],
+            error,
+        };
+        match self.error_policy {
+            ErrorPolicy::Panic => panic!("{}", error),
+            ErrorPolicy::SkipSystem => {
+                tracing::error!("{}, system disabled", error);
+                self.disabled.insert(type_id);
+            }
+            ErrorPolicy::DisableScene => {
+                tracing::error!("{}, scene disabled", error);
+                self.enabled = false;
+                if ready && !self.failed_readys.contains(&type_id) {
+                    self.failed_readys.push(type_id);
+                }
+            }
+        }
+        if self.errors.len() == MAX_ERRORS {
+            self.errors.remove(0);
+        }
+        self.errors.push(error);
+    }
+
+    /// registered computes, `Render::paint` run them for every scene before any paint.
+    /// each is recorded into its own command buffer, finished inside its error scopes since a
+    /// failing pass invalidates its encoder (wgpu reports it on finish), then all of them go in
+    /// one queue submit
+    pub fn compute(&mut self, gfx: &Gfx) {
+        if self.computes.is_empty() {
+            return;
+        }
+        let _span = tracing::debug_span!("compute", scene = %self.name).entered();
+        gfx.enter_scene(&self.name);
+        let mut command_buffers = Vec::new();
+        for compute in self.computes.iter() {
+            if self.disabled.contains(&compute.type_id) {
+                continue;
+            }
+            let _span = tracing::trace_span!("system", system = compute.name).entered();
+            push_error_scopes(gfx);
+            let mut computes = gfx.begin_compute();
+            let start = self.timings.start();
+            let [x, y, z] = (compute.dispatch)(&self.res);
+            let compute_pass = computes.begin_compute_pass(compute.name);
+            let mut compute_pass = (compute.compute)(&mut self.res, compute_pass);
+            if x > 0 && y > 0 && z > 0 {
+                compute_pass.dispatch_workgroups(x, y, z);
+            }
+            drop(compute_pass);
+            computes.end_pass();
+            self.timings.record(compute.type_id, "compute", start);
+            command_buffers.push(gfx.finish_compute(computes));
+            pop_error_scopes(gfx, compute.type_id, &mut self.error_scopes);
+        }
+        if !command_buffers.is_empty() {
+            gfx.submit_command_buffers(command_buffers);
+        }
+    }
+}
+
+/// remove the resolved scopes, with the errors they caught
+fn resolved_errors(scopes: &mut Vec<(TypeId, ErrorScope)>) -> Vec<(TypeId, wgpu::Error)> {
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut errors = Vec::new();
+    let mut index = 0;
+    while index < scopes.len() {
+        let Poll::Ready(error) = scopes[index].1.as_mut().poll(&mut cx) else {
+            index += 1;
+            continue;
+        };
+        let (type_id, _) = scopes.remove(index);
+        errors.extend(error.map(|error| (type_id, error)));
+    }
+    errors
+}
+
+fn push_error_scopes(gfx: &Gfx) {
+    gfx.device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+    gfx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+}
+
+fn pop_error_scopes(gfx: &Gfx, type_id: TypeId, scopes: &mut Vec<(TypeId, ErrorScope)>) {
+    scopes.push((type_id, Box::pin(gfx.device.pop_error_scope())));
+    scopes.push((type_id, Box::pin(gfx.device.pop_error_scope())));
+}
+
+fn run_hooks(
+    hooks: &[StateHook],
+    res: &mut HashTypeId2Data,
+    gfx: &Gfx,
+    disabled: &HashSet<TypeId>,
+    scopes: &mut Vec<(TypeId, ErrorScope)>,
+    failed: &mut Vec<(TypeId, Box<dyn std::error::Error>)>,
+    timings: &mut SystemTimings,
+) {
+    for hook in hooks.iter() {
+        if disabled.contains(&hook.type_id) || !(hook.when)(res) {
+            continue;
+        }
+        let _span = tracing::trace_span!("system", system = hook.name).entered();
+        push_error_scopes(gfx);
+        let start = timings.start();
+        let result = (hook.paint)(res, gfx);
+        timings.record(hook.type_id, "hook", start);
+        if let Err(error) = result {
+            failed.push((hook.type_id, error));
+        }
+        pop_error_scopes(gfx, hook.type_id, scopes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refs_muts_reach_globals() {
+        use crate::multi::{refs_muts, Mut, Ref};
+        /// a global that isn't a `Ready` system
+        struct Score(u32);
+        #[derive(Default)]
+        struct Player {
+            points: u32,
+        }
+        impl Ready for Player {
+            fn ready(&mut self, _: &mut HashTypeId2Data, _: &Gfx) {}
+        }
+        struct Scoring;
+        impl Queue for Scoring {
+            fn introduce(scene: &mut Scene) {
+                scene.add_ready(Player::default());
+            }
+        }
+        // the body of a Paint, `Scene::paint` itself needs a gpu
+        fn score(data: &mut HashTypeId2Data) {
+            let (player, score) = refs_muts::<(Ref<Player>, Mut<Score>)>(data);
+            score.0 += player.points;
+        }
+
+        let mut render = crate::Render::new();
+        render.insert_global(Score(1)).add_scene::<Scoring>("main");
+        return_res(&mut render.scenes[0].res, Player { points: 3 });
+        crate::with_globals(&mut render.globals, &mut render.scenes[0], |scene| {
+            score(&mut scene.res)
+        });
+        assert_eq!(render.global::<Score>().unwrap().0, 4);
+    }
+
+    #[test]
+    fn globals_shadowed_by_scene() {
+        let mut globals = HashTypeId2Data::new();
+        return_res(&mut globals, 1u32);
+        return_res(&mut globals, "global");
+        let mut scene = Scene::new("test".into());
+        return_res(&mut scene.res, "local");
+
+        let lent = scene.lend_globals(&mut globals);
+        assert_eq!(*get_res::<u32>(&scene.res), 1);
+        assert_eq!(*get_res::<&str>(&scene.res), "local");
+        *get_res_mut::<u32>(&mut scene.res) = 2;
+        scene.return_globals(&mut globals, lent);
+
+        assert_eq!(*get_res::<u32>(&globals), 2);
+        assert_eq!(*get_res::<&str>(&globals), "global");
+        assert!(!scene.res.contains_key(&TypeId::of::<u32>()));
+    }
+
+    #[test]
+    fn error_policy() {
+        struct Failing;
+        let type_id = TypeId::of::<Failing>();
+        let mut scene = Scene::new("test".into());
+        scene.system_names.insert(type_id, "Failing");
+        scene.set_error_policy(ErrorPolicy::SkipSystem);
+        for i in 0..MAX_ERRORS + 1 {
+            scene.report(type_id, format!("failed {}", i).into(), false);
+        }
+        assert_eq!(scene.errors().len(), MAX_ERRORS);
+        assert_eq!(
+            scene.last_error().unwrap().to_string(),
+            format!(
+                "error in scene `test` system `Failing`: failed {}",
+                MAX_ERRORS
+            )
+        );
+        assert_eq!(scene.disabled_systems().collect::<Vec<_>>(), ["Failing"]);
+        assert!(scene.is_enabled());
+
+        scene.set_error_policy(ErrorPolicy::DisableScene);
+        scene.report(type_id, "failed".into(), false);
+        assert!(!scene.is_enabled());
+        assert_eq!(scene.take_errors().len(), MAX_ERRORS);
+        assert!(scene.errors().is_empty());
+        scene.set_enabled(true);
+        assert!(scene.is_enabled());
+    }
+
+    #[test]
+    fn overlay_queues_cleared_without_draw() {
+        use crate::debug_draw::{DebugDraw, WHITE};
+        use crate::text::Text;
+        let mut scene = Scene::new("test".into());
+        scene.add_overlay(DebugDraw::default());
+        scene.add_overlay(Text::default());
+        for _ in 0..2 {
+            // a frame whose Paint never reaches `draw_overlays`
+            let debug = get_res_mut::<DebugDraw>(&mut scene.res);
+            debug.axes([0., 0., 0.], 1.);
+            debug.rect([0., 0.], [10., 10.], WHITE);
+            assert!(!debug.is_empty());
+            let text = get_res_mut::<Text>(&mut scene.res);
+            text.text("fps 60", [8., 8.]);
+            assert!(!text.is_empty());
+            scene.clear_overlays();
+        }
+        assert!(get_res::<DebugDraw>(&scene.res).is_empty());
+        assert!(get_res::<Text>(&scene.res).is_empty());
+    }
+
+    #[test]
+    fn timing_report_phases() {
+        struct Simulate;
+        impl<'a> Compute<'a> for Simulate {
+            fn compute(
+                _: &mut HashTypeId2Data,
+                compute_pass: wgpu::ComputePass<'a>,
+            ) -> wgpu::ComputePass<'a> {
+                compute_pass
+            }
+        }
+        struct Spawn;
+        impl Paint for Spawn {
+            fn paint(_: &mut HashTypeId2Data, _: &Gfx) {}
+        }
+        let mut scene = Scene::new("test".into());
+        scene
+            .set_system_timing(true)
+            .add_state(0u8)
+            .add_compute::<Simulate>(|_| [1, 1, 1])
+            .add_on_enter::<Spawn, _>(1u8)
+            .add_on_exit::<Spawn, _>(1u8)
+            .add_paint::<Spawn>();
+        // what `compute` and the state hooks record, they need a gpu to run
+        scene.timings.push(TypeId::of::<Simulate>(), "compute", 1.);
+        scene.timings.push(TypeId::of::<Spawn>(), "hook", 2.);
+        scene.timings.push(TypeId::of::<Spawn>(), "paint", 3.);
+        let rows: Vec<_> = scene
+            .timing_report()
+            .entries
+            .into_iter()
+            .map(|e| (e.phase, e.last_ms))
+            .collect();
+        assert_eq!(rows, [("compute", 1.), ("hook", 2.), ("paint", 3.)]);
+    }
+
+    #[test]
+    fn failed_ready_keeps_the_scene_disabled() {
+        struct Failing;
+        let type_id = TypeId::of::<Failing>();
+        let mut scene = Scene::new("test".into());
+        scene.system_names.insert(type_id, "Failing");
+        scene.set_error_policy(ErrorPolicy::DisableScene);
+        scene.report(type_id, "failed".into(), true);
+        scene.report(type_id, "failed again".into(), true);
+        assert_eq!(scene.failed_readys().collect::<Vec<_>>(), ["Failing"]);
+        scene.set_enabled(true);
+        assert!(!scene.is_enabled());
+        // what a successful `retry_readys` leaves
+        scene.failed_readys.clear();
+        scene.set_enabled(true);
+        assert!(scene.is_enabled());
+    }
+
+    #[test]
+    fn disabled_by_error_after_the_failing_system() {
+        struct Failing;
+        let type_id = TypeId::of::<Failing>();
+        let mut scene = Scene::new("test".into());
+        scene.system_names.insert(type_id, "Failing");
+        scene.set_error_policy(ErrorPolicy::SkipSystem);
+        scene.failed.push((type_id, "failed".into()));
+        // handled at the end of the paint, not after each system
+        assert!(!scene.disabled_by_error());
+        assert_eq!(scene.failed.len(), 1);
+        scene.set_error_policy(ErrorPolicy::DisableScene);
+        assert!(scene.disabled_by_error());
+        assert!(scene.failed.is_empty());
+        assert_eq!(scene.failed_readys().count(), 0);
+    }
 }
@@ -0,0 +1,124 @@
+/// current value of a user enum, register with `Scene::add_state(initial)`.
+/// systems request a transition with `set`, `Scene::paint` apply it before the next paints:
+/// ```ignore
+/// #[derive(Clone, Copy, PartialEq)]
+/// enum Phase { Loading, Menu, Running }
+///
+/// scene
+///     .add_state(Phase::Loading)
+///     .add_paint_in::<Menu, _>(Phase::Menu)
+///     .add_on_enter::<SpawnWorld, _>(Phase::Running)
+///     .add_on_exit::<SaveWorld, _>(Phase::Running);
+///
+/// // in a paint
+/// get_res_mut::<State<Phase>>(data).set(Phase::Running);
+/// ```
+/// the initial state is entered on the first paint
+pub struct State<S> {
+    current: S,
+    next: Option<S>,
+    /// from (none for the initial enter) and to, while the hooks run
+    transition: Option<(Option<S>, S)>,
+    entered: bool,
+}
+
+impl<S: Copy + PartialEq> State<S> {
+    pub fn new(initial: S) -> Self {
+        State {
+            current: initial,
+            next: None,
+            transition: None,
+            entered: false,
+        }
+    }
+
+    pub fn get(&self) -> S {
+        self.current
+    }
+
+    pub fn is(&self, state: S) -> bool {
+        self.current == state
+    }
+
+    /// applied between frames, the last request wins, setting the current state does nothing
+    pub fn set(&mut self, next: S) {
+        self.next = Some(next);
+    }
+
+    pub fn pending(&self) -> Option<S> {
+        self.next
+    }
+
+    /// true when there is a transition to run the hooks of
+    pub(crate) fn begin_transition(&mut self) -> bool {
+        if !self.entered {
+            self.entered = true;
+            let to = self.next.take().unwrap_or(self.current);
+            self.transition = Some((None, to));
+            return true;
+        }
+        match self.next.take() {
+            Some(next) if next != self.current => {
+                self.transition = Some((Some(self.current), next));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// between the exit and the enter hooks
+    pub(crate) fn switch(&mut self) {
+        if let Some((_, to)) = self.transition {
+            self.current = to;
+        }
+    }
+
+    pub(crate) fn end_transition(&mut self) {
+        self.transition = None;
+    }
+
+    pub(crate) fn exiting(&self, state: S) -> bool {
+        matches!(self.transition, Some((Some(from), _)) if from == state)
+    }
+
+    pub(crate) fn entering(&self, state: S) -> bool {
+        matches!(self.transition, Some((_, to)) if to == state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Phase {
+        Menu,
+        Running,
+    }
+
+    #[test]
+    fn transitions() {
+        let mut state = State::new(Phase::Menu);
+        assert!(state.begin_transition());
+        assert!(state.entering(Phase::Menu) && !state.exiting(Phase::Menu));
+        state.switch();
+        state.end_transition();
+        assert!(!state.begin_transition());
+
+        state.set(Phase::Running);
+        assert_eq!(
+            (state.get(), state.pending()),
+            (Phase::Menu, Some(Phase::Running))
+        );
+        assert!(state.begin_transition());
+        assert!(state.exiting(Phase::Menu) && state.entering(Phase::Running));
+        state.switch();
+        assert!(state.is(Phase::Running));
+        state.end_transition();
+        assert!(!state.entering(Phase::Running));
+
+        state.set(Phase::Running);
+        assert!(!state.begin_transition());
+        assert_eq!(state.pending(), None);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// largest side the atlas grows to
+const MAX_SIZE: u32 = 4096;
+/// empty pixels around each glyph so linear sampling doesn't bleed
+const PADDING: u32 = 1;
+
+struct Entry {
+    rect: [u32; 4],
+    bitmap: Vec<u8>,
+}
+
+/// single channel glyph atlas packed in shelves (rows) on the cpu,
+/// when full it doubles its size and repacks every glyph it has seen
+pub(crate) struct GlyphAtlas<K> {
+    size: u32,
+    pixels: Vec<u8>,
+    cursor: [u32; 2],
+    row_height: u32,
+    entries: HashMap<K, Entry>,
+    /// pixels changed since the last upload
+    pub dirty: bool,
+}
+
+impl<K: Hash + Eq + Copy> GlyphAtlas<K> {
+    pub fn new(size: u32) -> Self {
+        GlyphAtlas {
+            size,
+            pixels: vec![0; (size * size) as usize],
+            cursor: [PADDING, PADDING],
+            row_height: 0,
+            entries: HashMap::new(),
+            dirty: true,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// x, y, width, height in pixels
+    pub fn get(&self, key: &K) -> Option<[u32; 4]> {
+        self.entries.get(key).map(|e| e.rect)
+    }
+
+    /// false if the glyph doesn't fit even at the max size
+    pub fn insert(&mut self, key: K, width: u32, height: u32, bitmap: Vec<u8>) -> bool {
+        loop {
+            if let Some([x, y]) = self.pack(width, height) {
+                self.blit(x, y, width, height, &bitmap);
+                self.entries.insert(
+                    key,
+                    Entry {
+                        rect: [x, y, width, height],
+                        bitmap,
+                    },
+                );
+                return true;
+            }
+            if self.size >= MAX_SIZE {
+                return false;
+            }
+            self.grow();
+        }
+    }
+
+    fn pack(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        if self.cursor[0] + width + PADDING > self.size {
+            // next shelf
+            self.cursor = [PADDING, self.cursor[1] + self.row_height + PADDING];
+            self.row_height = 0;
+        }
+        if self.cursor[0] + width + PADDING > self.size
+            || self.cursor[1] + height + PADDING > self.size
+        {
+            return None;
+        }
+        let position = self.cursor;
+        self.cursor[0] += width + PADDING;
+        self.row_height = self.row_height.max(height);
+        Some(position)
+    }
+
+    fn blit(&mut self, x: u32, y: u32, width: u32, height: u32, bitmap: &[u8]) {
+        for row in 0..height {
+            let src = (row * width) as usize;
+            let dst = ((y + row) * self.size + x) as usize;
+            self.pixels[dst..dst + width as usize]
+                .copy_from_slice(&bitmap[src..src + width as usize]);
+        }
+        self.dirty = true;
+    }
+
+    fn grow(&mut self) {
+        let mut entries: Vec<(K, Entry)> = self.entries.drain().collect();
+        // tallest first packs shelves tighter
+        entries.sort_by(|a, b| b.1.rect[3].cmp(&a.1.rect[3]));
+        *self = GlyphAtlas::new(self.size * 2);
+        for (key, entry) in entries {
+            let [_, _, width, height] = entry.rect;
+            self.insert(key, width, height, entry.bitmap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlyphAtlas;
+
+    #[test]
+    fn grow_and_repack() {
+        let mut atlas = GlyphAtlas::new(16);
+        assert!(atlas.insert('a', 6, 6, vec![1; 36]));
+        assert!(atlas.insert('b', 6, 6, vec![2; 36]));
+        assert_eq!(atlas.size(), 16);
+        // third one doesn't fit on the first shelf nor a second one
+        assert!(atlas.insert('c', 10, 10, vec![3; 100]));
+        assert_eq!(atlas.size(), 32);
+        for (key, value) in [('a', 1), ('b', 2), ('c', 3)] {
+            let [x, y, w, _] = atlas.get(&key).unwrap();
+            assert_eq!(atlas.pixels()[(y * atlas.size() + x) as usize], value);
+            assert_eq!(
+                atlas.pixels()[(y * atlas.size() + x + w - 1) as usize],
+                value
+            );
+        }
+        assert!(!atlas.insert('d', 5000, 1, vec![0; 5000]));
+    }
+}
@@ -0,0 +1,482 @@
+use crate::{
+    debug_draw::{Color, WHITE},
+    gfx::{DynamicBuffer, Gfx},
+    scene::{get_res, get_res_mut, HashTypeId2Data, Overlay, Ready},
+};
+use atlas::GlyphAtlas;
+use fontdue::layout::{
+    CoordinateSystem, GlyphRasterConfig, HorizontalAlign, Layout, LayoutSettings, TextStyle,
+};
+use std::{error::Error, path::Path};
+
+mod atlas;
+
+const INITIAL_ATLAS_SIZE: u32 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// one string queued for this frame,
+/// `position` is the top left of the text box in screen pixels,
+/// with `max_width` lines wrap at word boundaries and `align` apply inside that width
+#[derive(Debug, Clone)]
+pub struct TextSection {
+    pub text: String,
+    pub position: [f32; 2],
+    pub size: f32,
+    pub color: Color,
+    pub align: Align,
+    pub max_width: Option<f32>,
+}
+
+impl TextSection {
+    pub fn new(text: impl Into<String>, position: [f32; 2]) -> Self {
+        TextSection {
+            text: text.into(),
+            position,
+            size: 16.0,
+            color: WHITE,
+            align: Align::Left,
+            max_width: None,
+        }
+    }
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct GlyphVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: Color,
+}
+
+/// screen space text drawn as an overlay by `scene::draw_overlays`,
+/// glyphs are rasterized from the font on first use into a gpu atlas that grows when full.
+/// ```ignore
+/// scene.add_overlay(Text::from_font_file("assets/font.ttf").unwrap());
+/// // in any Update or Paint
+/// get_res_mut::<Text>(data).queue(TextSection::new(format!("fps {:.0}", fps), [8., 8.]));
+/// ```
+/// sections not drawn by the end of the scene's paint are dropped
+pub struct Text {
+    font: Option<fontdue::Font>,
+    sections: Vec<TextSection>,
+    layout: Layout,
+    atlas: GlyphAtlas<GlyphRasterConfig>,
+    /// texture, its bind group and the atlas size it was created for
+    atlas_texture: Option<(wgpu::Texture, wgpu::BindGroup, u32)>,
+    bind_group_layout: Option<wgpu::BindGroupLayout>,
+    sampler: Option<wgpu::Sampler>,
+    pipeline: Option<wgpu::RenderPipeline>,
+    vertex_buffer: Option<DynamicBuffer>,
+    vertex_count: u32,
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text {
+            font: None,
+            sections: Vec::new(),
+            layout: Layout::new(CoordinateSystem::PositiveYDown),
+            atlas: GlyphAtlas::new(INITIAL_ATLAS_SIZE),
+            atlas_texture: None,
+            bind_group_layout: None,
+            sampler: None,
+            pipeline: None,
+            vertex_buffer: None,
+            vertex_count: 0,
+        }
+    }
+}
+
+impl Text {
+    /// TTF / OTF bytes
+    pub fn from_font_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())?;
+        Ok(Text {
+            font: Some(font),
+            ..Default::default()
+        })
+    }
+
+    pub fn from_font_file(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Self::from_font_bytes(&std::fs::read(path)?)
+    }
+
+    /// nothing queued since the last frame
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    pub fn queue(&mut self, section: TextSection) {
+        self.sections.push(section);
+    }
+
+    /// shortcut of `queue` with default size and color
+    pub fn text(&mut self, text: impl Into<String>, position: [f32; 2]) {
+        self.queue(TextSection::new(text, position));
+    }
+
+    /// lay out and clear the queued sections, rasterizing the glyphs not in the atlas yet.
+    /// the atlas may grow and move glyphs while filling, so quads keep the glyph key
+    fn layout_sections(&mut self) -> Vec<(GlyphRasterConfig, [f32; 2], Color)> {
+        let mut quads = Vec::new();
+        let Some(font) = self.font.as_ref() else {
+            self.sections.clear();
+            return quads;
+        };
+        for section in self.sections.drain(..) {
+            self.layout.reset(&LayoutSettings {
+                x: section.position[0],
+                y: section.position[1],
+                max_width: section.max_width,
+                horizontal_align: match section.align {
+                    Align::Left => HorizontalAlign::Left,
+                    Align::Center => HorizontalAlign::Center,
+                    Align::Right => HorizontalAlign::Right,
+                },
+                ..Default::default()
+            });
+            self.layout
+                .append(&[font], &TextStyle::new(&section.text, section.size, 0));
+            for glyph in self.layout.glyphs() {
+                if glyph.width == 0 || glyph.height == 0 {
+                    continue;
+                }
+                if self.atlas.get(&glyph.key).is_none() {
+                    let (metrics, bitmap) = font.rasterize_config(glyph.key);
+                    if !self.atlas.insert(
+                        glyph.key,
+                        metrics.width as u32,
+                        metrics.height as u32,
+                        bitmap,
+                    ) {
+                        tracing::warn!("Text: glyph atlas is full, skip {:?}", glyph.parent);
+                        continue;
+                    }
+                }
+                quads.push((glyph.key, [glyph.x, glyph.y], section.color));
+            }
+        }
+        quads
+    }
+
+    /// two triangles per glyph from its top left in screen pixels, with the uvs of its atlas rect
+    fn quad_vertices(
+        &self,
+        quads: &[(GlyphRasterConfig, [f32; 2], Color)],
+        [width, height]: [f32; 2],
+    ) -> Vec<GlyphVertex> {
+        let atlas_size = self.atlas.size() as f32;
+        let mut vertices = Vec::with_capacity(quads.len() * 6);
+        for &(key, [x, y], color) in quads {
+            let Some([ax, ay, aw, ah]) = self.atlas.get(&key) else {
+                continue;
+            };
+            let (x0, y0) = (x / width * 2. - 1., 1. - y / height * 2.);
+            let (x1, y1) = (
+                (x + aw as f32) / width * 2. - 1.,
+                1. - (y + ah as f32) / height * 2.,
+            );
+            let (u0, v0) = (ax as f32 / atlas_size, ay as f32 / atlas_size);
+            let (u1, v1) = ((ax + aw) as f32 / atlas_size, (ay + ah) as f32 / atlas_size);
+            let corner = |position, uv| GlyphVertex {
+                position,
+                uv,
+                color,
+            };
+            vertices.extend([
+                corner([x0, y0], [u0, v0]),
+                corner([x0, y1], [u0, v1]),
+                corner([x1, y1], [u1, v1]),
+                corner([x0, y0], [u0, v0]),
+                corner([x1, y1], [u1, v1]),
+                corner([x1, y0], [u1, v0]),
+            ]);
+        }
+        vertices
+    }
+
+    fn create_atlas_texture(&mut self, gfx: &Gfx) {
+        let size = self.atlas.size();
+        let texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("text atlas"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text atlas bind group"),
+            layout: self.bind_group_layout.as_ref().unwrap(),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(self.sampler.as_ref().unwrap()),
+                },
+            ],
+        });
+        self.atlas_texture = Some((texture, bind_group, size));
+        self.atlas.dirty = true;
+    }
+}
+
+impl Ready for Text {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("text atlas bind group layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                    ],
+                });
+        let shader = gfx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("text shader"),
+                source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SHADER)),
+            });
+        let pipeline_layout = gfx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("text pipeline layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline = gfx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("text pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<GlyphVertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4],
+                    }],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: gfx.surface_config.as_ref().unwrap().view_formats[0],
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+        let sampler = gfx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("text atlas sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let vertex_buffer = DynamicBuffer::new(
+            &gfx.device,
+            "text vertex buffer",
+            wgpu::BufferUsages::VERTEX,
+            6 * 256 * std::mem::size_of::<GlyphVertex>() as wgpu::BufferAddress,
+        );
+
+        let text = get_res_mut::<Text>(data);
+        // the font given to add_overlay lives in self, the resource is a default one
+        if text.font.is_none() {
+            text.font = self.font.clone();
+        }
+        text.bind_group_layout = Some(bind_group_layout);
+        text.sampler = Some(sampler);
+        text.pipeline = Some(pipeline);
+        text.vertex_buffer = Some(vertex_buffer);
+        text.create_atlas_texture(gfx);
+    }
+}
+
+impl Overlay for Text {
+    fn prepare(data: &mut HashTypeId2Data, gfx: &Gfx, encoder: &mut wgpu::CommandEncoder) {
+        let text = get_res_mut::<Text>(data);
+        let quads = text.layout_sections();
+
+        if text
+            .atlas_texture
+            .as_ref()
+            .is_some_and(|(_, _, size)| *size != text.atlas.size())
+        {
+            text.create_atlas_texture(gfx);
+        }
+        if let Some((texture, _, size)) = text.atlas_texture.as_ref().filter(|_| text.atlas.dirty) {
+            gfx.queue.write_texture(
+                texture.as_image_copy(),
+                text.atlas.pixels(),
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(*size),
+                    rows_per_image: Some(*size),
+                },
+                texture.size(),
+            );
+            text.atlas.dirty = false;
+        }
+
+        let screen = gfx
+            .surface_config
+            .as_ref()
+            .map(|c| [c.width as f32, c.height as f32])
+            .unwrap_or([1., 1.]);
+        let vertices = text.quad_vertices(&quads, screen);
+        text.vertex_count = vertices.len() as u32;
+        if let Some(buffer) = text.vertex_buffer.as_mut() {
+            buffer.write_staged(gfx, encoder, bytemuck::cast_slice(&vertices));
+        }
+    }
+
+    fn draw<'a>(
+        data: &mut HashTypeId2Data,
+        mut render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a> {
+        let text = get_res::<Text>(data);
+        if text.vertex_count == 0 {
+            return render_pass;
+        }
+        render_pass.set_pipeline(text.pipeline.as_ref().unwrap());
+        render_pass.set_bind_group(0, &text.atlas_texture.as_ref().unwrap().1, &[]);
+        render_pass.set_vertex_buffer(0, text.vertex_buffer.as_ref().unwrap().slice());
+        render_pass.draw(0..text.vertex_count, 0..1);
+        render_pass
+    }
+
+    fn clear(data: &mut HashTypeId2Data) {
+        get_res_mut::<Text>(data).sections.clear();
+    }
+}
+
+const SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    out.color = color;
+    return out;
+}
+
+@group(0) @binding(0) var atlas: texture_2d<f32>;
+@group(0) @binding(1) var atlas_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(atlas, atlas_sampler, in.uv).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(glyph_index: u16) -> GlyphRasterConfig {
+        GlyphRasterConfig {
+            glyph_index,
+            px: 16.,
+            font_hash: 0,
+        }
+    }
+
+    #[test]
+    fn glyph_quads_follow_the_atlas() {
+        let mut text = Text::default();
+        text.text("no font", [0., 0.]);
+        assert!(text.layout_sections().is_empty());
+        assert!(text.sections.is_empty());
+
+        assert!(text.atlas.insert(key(1), 8, 16, vec![255; 8 * 16]));
+        let quads = [(key(1), [128., 64.], WHITE), (key(2), [0., 0.], WHITE)];
+        let vertices = text.quad_vertices(&quads, [256., 128.]);
+        // the glyph not in the atlas is skipped
+        assert_eq!(vertices.len(), 6);
+        let [x, y, w, h] = text.atlas.get(&key(1)).unwrap();
+        let size = INITIAL_ATLAS_SIZE as f32;
+        assert_eq!(vertices[0].position, [0., 0.]);
+        assert_eq!(vertices[0].uv, [x as f32 / size, y as f32 / size]);
+        assert_eq!(vertices[2].position, [0.0625, -0.25]);
+        assert_eq!(
+            vertices[2].uv,
+            [(x + w) as f32 / size, (y + h) as f32 / size]
+        );
+
+        // growing repacks the glyph, the next quads sample it at its new place
+        assert!(text.atlas.insert(key(3), 300, 8, vec![255; 300 * 8]));
+        assert_eq!(text.atlas.size(), INITIAL_ATLAS_SIZE * 2);
+        let [x, y, ..] = text.atlas.get(&key(1)).unwrap();
+        let vertices = text.quad_vertices(&quads, [256., 128.]);
+        let size = size * 2.;
+        assert_eq!(vertices[0].uv, [x as f32 / size, y as f32 / size]);
+    }
+}
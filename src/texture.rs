@@ -0,0 +1,363 @@
+use crate::gfx::Gfx;
+use image::{
+    error::{LimitError, LimitErrorKind, ParameterError, ParameterErrorKind},
+    ImageError, ImageResult,
+};
+use std::{collections::HashMap, path::Path};
+
+/// color data (albedo, ui) is sRGB, data textures (normal map, roughness) are linear
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+impl ColorSpace {
+    pub fn format(self) -> wgpu::TextureFormat {
+        match self {
+            ColorSpace::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+/// 2d rgba8 texture with full mip chain and a default linear sampler
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub size: wgpu::Extent3d,
+}
+
+impl Texture {
+    /// decode PNG / JPEG bytes
+    pub fn from_bytes(
+        gfx: &Gfx,
+        bytes: &[u8],
+        label: &str,
+        color_space: ColorSpace,
+    ) -> image::ImageResult<Self> {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba8(gfx, &image, width, height, label, color_space)
+    }
+
+    /// read and decode PNG / JPEG file from local disk
+    pub fn from_path(
+        gfx: &Gfx,
+        path: impl AsRef<Path>,
+        color_space: ColorSpace,
+    ) -> image::ImageResult<Self> {
+        let path = path.as_ref();
+        let image = image::open(path)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba8(
+            gfx,
+            &image,
+            width,
+            height,
+            &path.to_string_lossy(),
+            color_space,
+        )
+    }
+
+    /// upload raw rgba8 pixels and generate the mip chain on gpu.
+    /// errors when a side is zero or above the device limit, or `rgba` isn't `4 * width * height` bytes
+    pub fn from_rgba8(
+        gfx: &Gfx,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        label: &str,
+        color_space: ColorSpace,
+    ) -> ImageResult<Self> {
+        check_rgba8(
+            rgba.len(),
+            width,
+            height,
+            gfx.device.limits().max_texture_dimension_2d,
+        )?;
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let mip_level_count = size.max_mips(wgpu::TextureDimension::D2);
+        let texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: color_space.format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        gfx.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        generate_mipmaps(gfx, &texture, mip_level_count);
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = gfx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        Ok(Texture {
+            texture,
+            view,
+            sampler,
+            size,
+        })
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    /// binding 0: texture_2d<f32>, binding 1: sampler, visible in fragment
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Texture Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    /// layout should come from `Texture::bind_group_layout`
+    pub fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Texture Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+}
+
+fn check_rgba8(len: usize, width: u32, height: u32, max_dimension: u32) -> ImageResult<()> {
+    if width == 0 || height == 0 || width > max_dimension || height > max_dimension {
+        return Err(ImageError::Limits(LimitError::from_kind(
+            LimitErrorKind::DimensionError,
+        )));
+    }
+    if len as u64 != 4 * width as u64 * height as u64 {
+        return Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::DimensionMismatch,
+        )));
+    }
+    Ok(())
+}
+
+/// blit pipeline of one texture format, built on the first texture of that format
+struct MipmapPipeline {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+}
+
+/// kept on `Gfx` so loading many textures doesn't rebuild the pipeline each time
+#[derive(Default)]
+pub(crate) struct MipmapPipelines(HashMap<wgpu::TextureFormat, MipmapPipeline>);
+
+/// render each mip level by sampling the previous one with a linear filter
+fn generate_mipmaps(gfx: &Gfx, texture: &wgpu::Texture, mip_level_count: u32) {
+    if mip_level_count < 2 {
+        return;
+    }
+    let device = &gfx.device;
+    let mut pipelines = gfx.mipmaps.lock().unwrap();
+    let MipmapPipeline {
+        pipeline,
+        layout,
+        sampler,
+    } = pipelines
+        .0
+        .entry(texture.format())
+        .or_insert_with(|| mipmap_pipeline(device, texture.format()));
+    let views: Vec<wgpu::TextureView> = (0..mip_level_count)
+        .map(|level| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Mipmap View"),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Mipmap Encoder"),
+    });
+    for pair in views.windows(2) {
+        let (src, dst) = (&pair[0], &pair[1]);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(src),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Mipmap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: dst,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+    gfx.queue.submit(Some(encoder.finish()));
+}
+
+fn mipmap_pipeline(device: &wgpu::Device, format: wgpu::TextureFormat) -> MipmapPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Mipmap Shader"),
+        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(MIPMAP_SHADER)),
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Mipmap Pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(format.into())],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+    let layout = pipeline.get_bind_group_layout(0);
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Mipmap Sampler"),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+    MipmapPipeline {
+        pipeline,
+        layout,
+        sampler,
+    }
+}
+
+const MIPMAP_SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// one triangle covering the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(src, src_sampler, in.uv);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgba8_size() {
+        assert!(check_rgba8(16, 2, 2, 8192).is_ok());
+        assert!(matches!(
+            check_rgba8(0, 0, 2, 8192),
+            Err(ImageError::Limits(_))
+        ));
+        assert!(matches!(
+            check_rgba8(4 * 9000, 9000, 1, 8192),
+            Err(ImageError::Limits(_))
+        ));
+        assert!(matches!(
+            check_rgba8(15, 2, 2, 8192),
+            Err(ImageError::Parameter(_))
+        ));
+    }
+}
@@ -19,3 +19,251 @@ pub fn now() -> TimeStamp {
         .now()
         / 1000.) as f32
 }
+
+/// seconds elapsed since `start`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn seconds_since(start: TimeStamp) -> f32 {
+    start.elapsed().as_secs_f32()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn seconds_since(start: TimeStamp) -> f32 {
+    now() - start
+}
+
+/// frame clock advanced once per frame by `Render::paint`, read it from any system through `gfx.time`.
+/// `delta` and `elapsed` are scaled, clamped to `max_delta` and stop while paused,
+/// the `real_*` ones are wall clock
+pub struct Time {
+    last_tick: Option<TimeStamp>,
+    delta: f32,
+    real_delta: f32,
+    elapsed: f32,
+    real_elapsed: f32,
+    frame_count: u64,
+    scale: f32,
+    paused: bool,
+    max_delta: f32,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Time {
+            last_tick: None,
+            delta: 0.,
+            real_delta: 0.,
+            elapsed: 0.,
+            real_elapsed: 0.,
+            frame_count: 0,
+            scale: 1.,
+            paused: false,
+            max_delta: 0.25,
+        }
+    }
+}
+
+impl Time {
+    /// advance by the wall time since the last tick, the first tick has a zero delta
+    pub fn tick(&mut self) {
+        let raw_delta = self.last_tick.map(seconds_since).unwrap_or(0.);
+        self.last_tick = Some(now());
+        self.advance(raw_delta);
+    }
+
+    /// advance by `raw_delta` seconds of wall time
+    pub fn advance(&mut self, raw_delta: f32) {
+        self.real_delta = raw_delta;
+        self.real_elapsed += raw_delta;
+        self.frame_count += 1;
+        self.delta = if self.paused {
+            0.
+        } else {
+            raw_delta.min(self.max_delta) * self.scale
+        };
+        self.elapsed += self.delta;
+    }
+
+    /// scaled seconds since the last frame
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    pub fn real_delta(&self) -> f32 {
+        self.real_delta
+    }
+
+    /// scaled seconds since the first frame, not advancing while paused
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub fn real_elapsed(&self) -> f32 {
+        self.real_elapsed
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// wall seconds since the last tick, 0 before the first one
+    pub fn since_tick(&self) -> f32 {
+        self.last_tick.map(seconds_since).unwrap_or(0.)
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// 0.5 is slow motion, 2 is fast forward
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.);
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn max_delta(&self) -> f32 {
+        self.max_delta
+    }
+
+    /// longest wall time a single frame may count for, so a stall (window drag, breakpoint) doesn't jump
+    pub fn set_max_delta(&mut self, max_delta: f32) {
+        self.max_delta = max_delta;
+    }
+
+    /// zero the delta of the current frame, e.g. after loading
+    pub fn zero_delta(&mut self) {
+        self.delta = 0.;
+    }
+}
+
+/// fixed timestep of a scene, registered systems with `Scene::add_fixed_update` run `steps`
+/// times per frame with `step` as their delta, whatever the frame rate.
+/// render time systems read `alpha` to interpolate between the last two steps:
+/// ```ignore
+/// let alpha = get_res::<FixedTime>(data).alpha();
+/// let angle = body.previous_angle + (body.angle - body.previous_angle) * alpha;
+/// ```
+pub struct FixedTime {
+    step: f32,
+    max_substeps: u32,
+    accumulator: f32,
+    steps: u32,
+    total_steps: u64,
+}
+
+impl Default for FixedTime {
+    /// 60 updates per second, at most 8 per frame
+    fn default() -> Self {
+        FixedTime::new(1. / 60.)
+    }
+}
+
+impl FixedTime {
+    pub fn new(step: f32) -> Self {
+        FixedTime {
+            step: step.max(f32::EPSILON),
+            max_substeps: 8,
+            accumulator: 0.,
+            steps: 0,
+            total_steps: 0,
+        }
+    }
+
+    /// whole steps left behind when a frame needs more are dropped, keeping only the fraction
+    /// toward the next step, so a slow frame doesn't make the next ones slower and slower
+    pub fn with_max_substeps(mut self, max_substeps: u32) -> Self {
+        self.max_substeps = max_substeps.max(1);
+        self
+    }
+
+    /// accumulate the frame delta, return how many steps to run now.
+    /// called by `Scene::paint` with `gfx.time.delta()`
+    pub fn accumulate(&mut self, delta: f32) -> u32 {
+        self.accumulator += delta.max(0.);
+        let steps = ((self.accumulator / self.step) as u32).min(self.max_substeps);
+        self.accumulator -= steps as f32 * self.step;
+        if steps == self.max_substeps {
+            self.accumulator %= self.step;
+        }
+        self.steps = steps;
+        self.total_steps += steps as u64;
+        steps
+    }
+
+    /// seconds per step, the delta of fixed updates
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    pub fn max_substeps(&self) -> u32 {
+        self.max_substeps
+    }
+
+    /// steps run in this frame
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    pub fn total_steps(&self) -> u64 {
+        self.total_steps
+    }
+
+    /// 0..1 progress of the accumulated time toward the next step
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0., 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedTime, Time};
+
+    #[test]
+    fn advance() {
+        let mut time = Time::default();
+        time.advance(0.1);
+        time.set_scale(2.);
+        time.advance(0.1);
+        assert_eq!(time.frame_count(), 2);
+        assert!((time.delta() - 0.2).abs() < 1e-6);
+        assert!((time.elapsed() - 0.3).abs() < 1e-6);
+        assert!((time.real_elapsed() - 0.2).abs() < 1e-6);
+
+        time.pause();
+        time.advance(0.1);
+        assert_eq!(time.delta(), 0.);
+        assert!((time.elapsed() - 0.3).abs() < 1e-6);
+        assert!((time.real_delta() - 0.1).abs() < 1e-6);
+
+        time.resume();
+        time.set_scale(1.);
+        time.advance(10.);
+        assert_eq!(time.delta(), time.max_delta());
+    }
+
+    #[test]
+    fn fixed_steps() {
+        let mut fixed = FixedTime::new(0.1).with_max_substeps(3);
+        assert_eq!(fixed.accumulate(0.05), 0);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-5);
+        assert_eq!(fixed.accumulate(0.17), 2);
+        assert!((fixed.alpha() - 0.2).abs() < 1e-5);
+        // capped, the late whole steps are dropped and the fraction kept
+        assert_eq!(fixed.accumulate(1.), 3);
+        assert_eq!(fixed.steps(), 3);
+        assert_eq!(fixed.total_steps(), 5);
+        assert!((fixed.alpha() - 0.2).abs() < 1e-4);
+        assert_eq!(fixed.accumulate(0.), 0);
+        assert_eq!(fixed.accumulate(0.09), 1);
+    }
+}
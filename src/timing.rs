@@ -0,0 +1,203 @@
+use crate::time::{now, seconds_since, TimeStamp};
+use std::{any::TypeId, collections::HashMap, collections::VecDeque, fmt};
+
+/// calls the rolling average is taken over
+const WINDOW: usize = 120;
+
+/// cpu wall time of one system in one phase
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingEntry {
+    pub scene: String,
+    /// type name of the Ready or Paint
+    pub system: &'static str,
+    /// `ready`, `fixed_update` or `paint`
+    pub phase: &'static str,
+    pub calls: u64,
+    pub last_ms: f32,
+    /// over the last 120 calls
+    pub average_ms: f32,
+    /// since timing was enabled or reset
+    pub worst_ms: f32,
+}
+
+/// cpu time of the systems of one or more scenes, see `Render::set_system_timing`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimingReport {
+    pub entries: Vec<TimingEntry>,
+}
+
+impl TimingReport {
+    /// average time of a frame spent in paints and fixed updates
+    pub fn frame_average_ms(&self) -> f32 {
+        self.entries
+            .iter()
+            .filter(|e| e.phase != "ready")
+            .map(|e| e.average_ms)
+            .sum()
+    }
+
+    /// slowest first by average
+    pub fn sorted_by_average(mut self) -> Self {
+        self.entries
+            .sort_by(|a, b| b.average_ms.total_cmp(&a.average_ms));
+        self
+    }
+}
+
+impl fmt::Display for TimingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scene_width = self
+            .entries
+            .iter()
+            .map(|e| e.scene.len())
+            .fold(5, usize::max);
+        let system_width = self
+            .entries
+            .iter()
+            .map(|e| e.system.len())
+            .fold(6, usize::max);
+        writeln!(
+            f,
+            "{:<scene_width$}  {:<system_width$}  {:<12}  {:>8}  {:>9}  {:>9}  {:>9}",
+            "scene", "system", "phase", "calls", "last ms", "avg ms", "worst ms"
+        )?;
+        for e in self.entries.iter() {
+            writeln!(
+                f,
+                "{:<scene_width$}  {:<system_width$}  {:<12}  {:>8}  {:>9.3}  {:>9.3}  {:>9.3}",
+                e.scene, e.system, e.phase, e.calls, e.last_ms, e.average_ms, e.worst_ms
+            )?;
+        }
+        write!(
+            f,
+            "{:<scene_width$}  {:<system_width$}  {:<12}  {:>8}  {:>9}  {:>9.3}",
+            "",
+            "frame",
+            "",
+            "",
+            "",
+            self.frame_average_ms()
+        )
+    }
+}
+
+/// rolling samples of one system
+#[derive(Default)]
+struct SystemTiming {
+    samples: VecDeque<f32>,
+    /// f64 so adding and removing samples over a long run doesn't drift
+    sum: f64,
+    calls: u64,
+    worst: f32,
+}
+
+impl SystemTiming {
+    fn push(&mut self, ms: f32) {
+        if self.samples.len() == WINDOW {
+            self.sum -= self.samples.pop_front().unwrap_or(0.) as f64;
+        }
+        self.samples.push_back(ms);
+        self.sum += ms as f64;
+        self.calls += 1;
+        self.worst = self.worst.max(ms);
+    }
+
+    fn average(&self) -> f32 {
+        if self.samples.is_empty() {
+            0.
+        } else {
+            (self.sum / self.samples.len() as f64) as f32
+        }
+    }
+}
+
+/// timings of the systems of one scene, off by default
+#[derive(Default)]
+pub(crate) struct SystemTimings {
+    enabled: bool,
+    systems: HashMap<(TypeId, &'static str), SystemTiming>,
+}
+
+impl SystemTimings {
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.systems.clear();
+    }
+
+    /// none when off, so a disabled profiler doesn't read the clock
+    pub(crate) fn start(&self) -> Option<TimeStamp> {
+        self.enabled.then(now)
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        type_id: TypeId,
+        phase: &'static str,
+        start: Option<TimeStamp>,
+    ) {
+        if let Some(start) = start {
+            self.push(type_id, phase, seconds_since(start) * 1000.);
+        }
+    }
+
+    pub(crate) fn push(&mut self, type_id: TypeId, phase: &'static str, ms: f32) {
+        self.systems.entry((type_id, phase)).or_default().push(ms);
+    }
+
+    /// the entry of a measured system, none when it never ran with timing on
+    pub(crate) fn entry(
+        &self,
+        scene: &str,
+        system: &'static str,
+        type_id: TypeId,
+        phase: &'static str,
+    ) -> Option<TimingEntry> {
+        let timing = self.systems.get(&(type_id, phase))?;
+        Some(TimingEntry {
+            scene: scene.to_string(),
+            system,
+            phase,
+            calls: timing.calls,
+            last_ms: timing.samples.back().copied().unwrap_or(0.),
+            average_ms: timing.average(),
+            worst_ms: timing.worst,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_average_and_worst() {
+        struct Paint;
+        let type_id = TypeId::of::<Paint>();
+        let mut timings = SystemTimings::default();
+        assert!(timings.start().is_none());
+        timings.push(type_id, "paint", 10.);
+        for _ in 0..WINDOW {
+            timings.push(type_id, "paint", 1.);
+        }
+        let entry = timings.entry("main", "Paint", type_id, "paint").unwrap();
+        assert_eq!(entry.calls, WINDOW as u64 + 1);
+        assert_eq!(entry.average_ms, 1.);
+        assert_eq!((entry.last_ms, entry.worst_ms), (1., 10.));
+        assert!(timings.entry("main", "Paint", type_id, "ready").is_none());
+
+        let report = TimingReport {
+            entries: vec![entry],
+        };
+        let table = report.to_string();
+        assert!(table.starts_with("scene  system  phase"));
+        assert!(table
+            .contains("main   Paint   paint              121      1.000      1.000     10.000"));
+    }
+}
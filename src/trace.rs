@@ -0,0 +1,75 @@
+use std::path::Path;
+use tracing::{subscriber::SetGlobalDefaultError, Level, Subscriber};
+use tracing_chrome::ChromeLayerBuilder;
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, Layer, Registry};
+
+pub use tracing_chrome::FlushGuard;
+
+/// set a global `tracing-chrome` subscriber writing the library spans (and any other) to path,
+/// open the json in `chrome://tracing` or https://ui.perfetto.dev to see the frame timeline:
+/// ```ignore
+/// let _guard = ready_paint::trace::chrome_trace("trace.json", Level::TRACE).unwrap();
+/// // ... run some frames, the file is complete once the guard drops
+/// ```
+/// library systems are `trace` spans and scenes `debug` spans, `Level::DEBUG` keeps scenes
+/// but not each system. the `scene` and `system` fields are in the args of each span
+pub fn chrome_trace(
+    path: impl AsRef<Path>,
+    max_level: Level,
+) -> Result<FlushGuard, SetGlobalDefaultError> {
+    let (subscriber, guard) = subscriber(ChromeLayerBuilder::new().file(path), max_level);
+    tracing::subscriber::set_global_default(subscriber)?;
+    Ok(guard)
+}
+
+fn subscriber(
+    builder: ChromeLayerBuilder<Registry>,
+    max_level: Level,
+) -> (impl Subscriber + Send + Sync, FlushGuard) {
+    let (layer, guard) = builder.include_args(true).build();
+    let subscriber =
+        tracing_subscriber::registry().with(layer.with_filter(LevelFilter::from_level(max_level)));
+    (subscriber, guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spans_and_events() {
+        let buffer = Buffer::default();
+        let (subscriber, guard) = subscriber(
+            ChromeLayerBuilder::new().writer(buffer.clone()),
+            Level::DEBUG,
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let _scene = tracing::debug_span!("paint", scene = %"main").entered();
+            let _system = tracing::trace_span!("system", system = "game::Player").entered();
+            tracing::warn!("atlas full");
+        });
+        drop(guard);
+        let json = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(json.contains("\"name\":\"paint\""));
+        assert!(json.contains("\"scene\":\"main\""));
+        assert!(json.contains("atlas full"));
+        assert!(!json.contains("game::Player"));
+    }
+}